@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+use toml::de::{DeTable, DeValue};
+
+/// The settings read from a TOML configuration file, as entries per table. Values are strings,
+/// integers, booleans or arrays of them, and nested and inline tables become tables of their own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    entries: Vec<Entry>,
+}
+
+/// A `key = value` pair and the table it is in, e.g. `keys.step` for a key under `[keys.step]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub table: String,
+    pub key: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+/// An error on the given 0-based line of the configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Config {
+    /// Parse a configuration.
+    /// ```
+    /// use busyboard::config::{Config, Value};
+    /// let config = Config::parse("
+    ///     speed = 10 # Hz
+    ///     [keys.step]
+    ///     step = \"space\"
+    ///     help = [\"?\", \"f1\"]
+    /// ").unwrap();
+    ///
+    /// assert_eq!(config.get("", "speed"), Some(&Value::Integer(10)));
+    /// assert_eq!(config.table("keys.step").count(), 2);
+    /// assert_eq!(config.get("keys.step", "step"), Some(&Value::String("space".into())));
+    ///
+    /// let config = Config::parse("
+    ///     keys = { step = { step = 'space' } }
+    ///     [devices]
+    ///     uart = [
+    ///         0xf0,
+    ///         0xf1,
+    ///     ]
+    /// ").unwrap();
+    /// assert_eq!(config.get("keys.step", "step"), Some(&Value::String("space".into())));
+    /// assert_eq!(config.get("devices", "uart"), Some(&Value::Array(vec![Value::Integer(0xf0), Value::Integer(0xf1)])));
+    ///
+    /// let error = Config::parse("[keys]\nstep = space").unwrap_err();
+    /// assert_eq!(error.line, 1);
+    ///
+    /// let error = Config::parse("[keys]\n[keys]").unwrap_err();
+    /// assert_eq!(error.line, 1);
+    /// ```
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let document = DeTable::parse(text).map_err(|error| ConfigError {
+            line: line_of(text, error.span().map_or(0, |span| span.start)),
+            message: error.message().to_string(),
+        })?;
+
+        let mut entries = vec![];
+        flatten(text, "", document.get_ref(), &mut entries)?;
+        Ok(Config { entries })
+    }
+
+    /// Read the configuration at the given path, or the default one when no path is given.
+    /// A missing default configuration is empty, while a missing given one is an error.
+    pub fn load(path: Option<PathBuf>) -> Result<Config, String> {
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text)
+                .map_err(|error| format!("{}:{}: {}", path.display(), error.line + 1, error.message)),
+            Err(error) if !required && error.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(error) => Err(format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    /// Returns the value of the given key in the given table, where `""` is the top level.
+    pub fn get(&self, table: &str, key: &str) -> Option<&Value> {
+        self.entries.iter().rev().find(|entry| entry.table == table && entry.key == key).map(|entry| &entry.value)
+    }
+
+    /// Returns the entries in the given table, in the order they were written.
+    pub fn table<'c>(&'c self, table: &'c str) -> impl Iterator<Item = &'c Entry> + 'c {
+        self.entries.iter().filter(move |entry| entry.table == table)
+    }
+
+    /// Returns the entries in tables nested in the given one, e.g. `keys.step` for `keys`.
+    pub fn tables<'c>(&'c self, parent: &'c str) -> impl Iterator<Item = (&'c str, &'c Entry)> + 'c {
+        self.entries.iter().filter_map(move |entry| {
+            entry.table.strip_prefix(parent).and_then(|name| name.strip_prefix('.')).map(|name| (name, entry))
+        })
+    }
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The value's elements if it is an array, or the value itself otherwise, so a single value
+    /// can be written where a list is expected.
+    pub fn as_list(&self) -> &[Value] {
+        match self {
+            Value::Array(values) => values,
+            value => std::slice::from_ref(value),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(values) => {
+                let values = values.iter().map(Value::to_string).collect::<Vec<_>>();
+                write!(f, "[{}]", values.join(", "))
+            },
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/busyboard/config.toml`, falling back to `~/.config`.
+fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(dir.join("busyboard").join("config.toml"))
+}
+
+/// Add the entries of the given table and the tables nested in it, in the order they were written.
+fn flatten(text: &str, name: &str, table: &DeTable, entries: &mut Vec<Entry>) -> Result<(), ConfigError> {
+    for (key, value) in table.iter() {
+        let line = line_of(text, key.span().start);
+
+        if let DeValue::Table(nested) = value.get_ref() {
+            let nested_name = if name.is_empty() { key.get_ref().to_string() } else { format!("{}.{}", name, key.get_ref()) };
+            flatten(text, &nested_name, nested, entries)?;
+        } else {
+            let value = convert(value.get_ref()).map_err(|message| ConfigError { line, message })?;
+            entries.push(Entry { table: name.to_string(), key: key.get_ref().to_string(), value, line });
+        }
+    }
+
+    Ok(())
+}
+
+fn convert(value: &DeValue) -> Result<Value, String> {
+    match value {
+        DeValue::String(s) => Ok(Value::String(s.to_string())),
+        DeValue::Integer(n) => i64::from_str_radix(n.as_str(), n.radix())
+            .map(Value::Integer)
+            .map_err(|_| format!("integer `{}` is out of range", n.as_str())),
+        DeValue::Boolean(b) => Ok(Value::Boolean(*b)),
+        DeValue::Array(values) => values.iter().map(|value| convert(value.get_ref())).collect::<Result<_, _>>().map(Value::Array),
+        value => Err(format!("unsupported {} value", value.type_str())),
+    }
+}
+
+/// Returns the 0-based line of the given byte offset.
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count()
+}
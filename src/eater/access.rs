@@ -0,0 +1,73 @@
+/// The ways in which the CPU can touch an address in RAM.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Opcode = 0,
+    Operand = 1,
+    Read = 2,
+    Write = 3,
+}
+
+/// Records how each address in RAM was accessed while executing a program.
+#[derive(Clone, Debug, Default)]
+pub struct AccessMap {
+    accesses: Vec<u8>,
+}
+
+impl AccessMap {
+    /// Returns true if the address was accessed in the given way.
+    /// ```
+    /// use busyboard::eater::{Access, Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x03),
+    ///     I::hlt(),
+    /// ], vec![0x2a]).with_access_map();
+    ///
+    /// cpu.step();
+    /// let accesses = cpu.access_map().unwrap();
+    /// assert!(accesses.get(0x00, Access::Opcode));
+    /// assert!(accesses.get(0x01, Access::Operand));
+    /// assert!(accesses.get(0x03, Access::Read));
+    /// assert!(!accesses.get(0x02, Access::Opcode));
+    /// ```
+    pub fn get(&self, adr: u8, access: Access) -> bool {
+        self.accesses.get(adr as usize).is_some_and(|a| a & (1 << access as u8) != 0)
+    }
+
+    /// Returns true if the address was fetched as part of an instruction.
+    pub fn executed(&self, adr: u8) -> bool {
+        self.get(adr, Access::Opcode) || self.get(adr, Access::Operand)
+    }
+
+    /// Returns true if the address was both executed and written, i.e. the program modified itself.
+    /// ```
+    /// use busyboard::eater::{Cpu, Flag, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x0f),
+    ///     I::sta(0x04),
+    ///     I::nop(),
+    /// ], vec![]).with_access_map();
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// cpu.step();
+    /// assert!(cpu.get(Flag::Halt));
+    /// assert!(cpu.access_map().unwrap().is_self_modified(0x04));
+    /// assert!(!cpu.access_map().unwrap().is_self_modified(0x00));
+    /// ```
+    pub fn is_self_modified(&self, adr: u8) -> bool {
+        self.executed(adr) && self.get(adr, Access::Write)
+    }
+
+    /// Forget all recorded accesses.
+    pub fn clear(&mut self) {
+        self.accesses.clear();
+    }
+
+    pub (super) fn record(&mut self, adr: u8, access: Access) {
+        if adr as usize >= self.accesses.len() {
+            self.accesses.resize(adr as usize + 1, 0);
+        }
+
+        self.accesses[adr as usize] |= 1 << access as u8;
+    }
+}
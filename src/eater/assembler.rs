@@ -0,0 +1,237 @@
+use super::{I, Instruction, Isa, supports};
+use std::collections::HashMap;
+
+/// The bytes assembled from a program's source, and the source line each byte came from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Assembly {
+    bytes: Vec<u8>,
+    lines: Vec<usize>,
+}
+
+/// An error on the given 0-based line of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+enum Operand<'s> {
+    Number(u8),
+    Label(&'s str),
+}
+
+enum Statement<'s> {
+    Instruction(&'s str, Option<Operand<'s>>),
+    Data(Vec<Operand<'s>>),
+}
+
+impl Assembly {
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the source line the byte at the given address was assembled from.
+    pub fn line(&self, adr: u8) -> Option<usize> {
+        self.lines.get(adr as usize).copied()
+    }
+}
+
+/// Assemble eater assembly. Each line holds an optional `label:`, then an instruction such as
+/// `lda count` or `db` followed by comma separated bytes. Mnemonics are case insensitive, operands
+/// are decimal, `0x` prefixed hexadecimal or labels, and `;` starts a comment. Instructions that
+/// `isa` doesn't have are errors, and so are `rti`, `ei` and `di` without `interrupts`.
+/// ```
+/// use busyboard::eater::{assemble, Cpu, I, Isa};
+/// let assembly = assemble("
+///     loop: lda count ; Count up forever
+///           add one
+///           sta count
+///           jmp loop
+///     one:   db 1
+///     count: db 0x00
+/// ", Isa::Basic, false).unwrap();
+///
+/// let cpu = Cpu::from_asm(vec![I::lda(9), I::add(8), I::sta(9), I::jmp(0)], vec![1, 0]);
+/// assert_eq!(assembly.bytes(), cpu.read_bytes(0, cpu.len()));
+/// assert_eq!(assembly.line(2), Some(2));
+/// assert_eq!(assembly.line(9), Some(6));
+///
+/// let errors = assemble("lda\nfoo 1\njmp nowhere", Isa::Basic, false).unwrap_err();
+/// assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), vec![0, 1, 2]);
+///
+/// let errors = assemble("call 0", Isa::Basic, false).unwrap_err();
+/// assert_eq!(errors[0].message, "`call` needs the extended instruction set");
+/// assert!(assemble("call 0", Isa::Extended, false).is_ok());
+///
+/// let errors = assemble("ei", Isa::Basic, false).unwrap_err();
+/// assert_eq!(errors[0].message, "`ei` needs interrupts");
+/// assert!(assemble("ei", Isa::Basic, true).is_ok());
+/// ```
+pub fn assemble(source: &str, isa: Isa, interrupts: bool) -> Result<Assembly, Vec<AsmError>> {
+    let mut errors = vec![];
+    let mut labels = HashMap::new();
+    let mut statements = vec![];
+    let mut adr = 0_usize;
+
+    for (line, text) in source.lines().enumerate() {
+        let mut text = text.split(';').next().unwrap_or("").trim();
+        let error = |message: String| AsmError { line, message };
+
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                errors.push(error(format!("invalid label `{}`", label)));
+            } else if labels.insert(label, adr).is_some() {
+                errors.push(error(format!("duplicate label `{}`", label)));
+            }
+
+            text = rest.trim();
+        }
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands = operands.split(',').map(str::trim).filter(|o| !o.is_empty()).collect::<Vec<_>>();
+        let operands = match operands.into_iter().map(parse_operand).collect::<Result<Vec<_>, _>>() {
+            Ok(operands) => operands,
+            Err(message) => {
+                errors.push(error(message));
+                continue;
+            },
+        };
+
+        let statement = if mnemonic.eq_ignore_ascii_case("db") {
+            if operands.is_empty() {
+                errors.push(error("`db` expects at least one byte".to_string()));
+                continue;
+            }
+
+            Statement::Data(operands)
+        } else {
+            let Some(size) = size(mnemonic) else {
+                errors.push(error(format!("unknown instruction `{}`", mnemonic)));
+                continue;
+            };
+            let parsed = instruction(mnemonic, 0);
+            if !supports(isa, interrupts, &parsed) {
+                let feature = if isa.supports(&parsed) { "interrupts" } else { "the extended instruction set" };
+                errors.push(error(format!("`{}` needs {}", mnemonic, feature)));
+                continue;
+            }
+
+            let mut operands = operands.into_iter();
+            let operand = operands.next();
+            if operand.is_some() != (size == 2) || operands.next().is_some() {
+                let expected = if size == 2 { "one operand" } else { "no operands" };
+                errors.push(error(format!("`{}` expects {}", mnemonic, expected)));
+                continue;
+            }
+
+            Statement::Instruction(mnemonic, operand)
+        };
+
+        let len = match &statement {
+            Statement::Instruction(mnemonic, _) => size(mnemonic).unwrap_or(1),
+            Statement::Data(bytes) => bytes.len(),
+        };
+        statements.push((line, statement));
+        adr += len;
+    }
+
+    if adr > 256 {
+        errors.push(AsmError { line: statements.last().map_or(0, |(line, _)| *line), message: format!("program is {} bytes, but only 256 fit in RAM", adr) });
+    }
+
+    let mut assembly = Assembly::default();
+    for (line, statement) in statements {
+        let resolve = |operand: &Operand| match operand {
+            Operand::Number(n) => Ok(*n),
+            Operand::Label(label) => match labels.get(label) {
+                Some(adr) if *adr <= 0xff => Ok(*adr as u8),
+                Some(_) => Err(AsmError { line, message: format!("label `{}` is past the end of RAM", label) }),
+                None => Err(AsmError { line, message: format!("undefined label `{}`", label) }),
+            },
+        };
+
+        let bytes = match statement {
+            Statement::Instruction(mnemonic, operand) => {
+                match operand.as_ref().map(resolve).transpose() {
+                    Ok(operand) => instruction(mnemonic, operand.unwrap_or(0)).assemble(),
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    },
+                }
+            },
+            Statement::Data(operands) => match operands.iter().map(resolve).collect::<Result<Vec<_>, _>>() {
+                Ok(bytes) => bytes,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                },
+            },
+        };
+
+        assembly.lines.extend(std::iter::repeat_n(line, bytes.len()));
+        assembly.bytes.extend(bytes);
+    }
+
+    if errors.is_empty() {
+        Ok(assembly)
+    } else {
+        errors.sort_by_key(|error| error.line);
+        Err(errors)
+    }
+}
+
+fn is_label(label: &str) -> bool {
+    label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_operand(operand: &str) -> Result<Operand<'_>, String> {
+    if is_label(operand) {
+        return Ok(Operand::Label(operand));
+    }
+
+    let parsed = match operand.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => operand.parse(),
+    };
+
+    parsed.map(Operand::Number).map_err(|_| format!("invalid operand `{}`", operand))
+}
+
+fn size(mnemonic: &str) -> Option<usize> {
+    match mnemonic.to_ascii_lowercase().as_str() {
+        "nop" | "in" | "out" | "hlt" | "rti" | "ei" | "di" | "ret" | "push" | "pop" => Some(1),
+        "ldi" | "lda" | "sta" | "add" | "sub" | "jmp" | "jpz" | "jpc" | "call" => Some(2),
+        _ => None,
+    }
+}
+
+fn instruction(mnemonic: &str, operand: u8) -> I {
+    match mnemonic.to_ascii_lowercase().as_str() {
+        "ldi" => I::ldi(operand),
+        "lda" => I::lda(operand),
+        "sta" => I::sta(operand),
+        "add" => I::add(operand),
+        "sub" => I::sub(operand),
+        "jmp" => I::jmp(operand),
+        "jpz" => I::jpz(operand),
+        "jpc" => I::jpc(operand),
+        "call" => I::call(operand),
+        "in" => I::input(),
+        "out" => I::out(),
+        "hlt" => I::hlt(),
+        "rti" => I::rti(),
+        "ei" => I::ei(),
+        "di" => I::di(),
+        "ret" => I::ret(),
+        "push" => I::push(),
+        "pop" => I::pop(),
+        _ => I::nop(),
+    }
+}
@@ -0,0 +1,286 @@
+use super::{disassemble_from, Assembly, Disassembly, I, Isa};
+use std::collections::BTreeMap;
+
+/// Which instructions a program executed, and which way each conditional jump went, over one or
+/// more runs.
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    executions: [u64; 256],
+    taken: [u64; 256],
+    not_taken: [u64; 256],
+}
+
+impl Coverage {
+    /// Returns how often the instruction at the given address was executed.
+    pub fn executions(&self, adr: u8) -> u64 {
+        self.executions[adr as usize]
+    }
+
+    /// Returns how often the conditional jump at the given address jumped.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x02),
+    ///     I::sub(0x09),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x01]).with_coverage();
+    ///
+    /// cpu.run();
+    /// let coverage = cpu.coverage().unwrap();
+    /// assert_eq!(coverage.executions(0x04), 2);
+    /// assert_eq!(coverage.taken(0x04), 1);
+    /// assert_eq!(coverage.not_taken(0x04), 1);
+    /// ```
+    pub fn taken(&self, adr: u8) -> u64 {
+        self.taken[adr as usize]
+    }
+
+    /// Returns how often the conditional jump at the given address fell through.
+    pub fn not_taken(&self, adr: u8) -> u64 {
+        self.not_taken[adr as usize]
+    }
+
+    /// Add the counts of another run of the same program, such as another case of a test suite.
+    /// ```
+    /// use busyboard::eater::{Coverage, Cpu, I};
+    /// let program = || Cpu::from_asm(vec![
+    ///     I::input(),
+    ///     I::jpz(0x05),
+    ///     I::out(),
+    ///     I::hlt(),
+    /// ], vec![]).with_coverage();
+    ///
+    /// let mut coverage = Coverage::default();
+    /// for input in [0, 1] {
+    ///     let mut cpu = program().with_in(move || input);
+    ///     cpu.run();
+    ///     coverage.merge(cpu.coverage().unwrap());
+    /// }
+    ///
+    /// assert_eq!(coverage.taken(0x01), 1);
+    /// assert_eq!(coverage.not_taken(0x01), 1);
+    /// assert_eq!(coverage.executions(0x03), 1);
+    /// ```
+    pub fn merge(&mut self, other: &Coverage) {
+        for adr in 0..256 {
+            self.executions[adr] += other.executions[adr];
+            self.taken[adr] += other.taken[adr];
+            self.not_taken[adr] += other.not_taken[adr];
+        }
+    }
+
+    /// Forget all counts.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// A summary of the program in `bytes`, decoded with `isa`: how many of its instructions and
+    /// branch directions were covered, then each instruction and direction that wasn't. The
+    /// program's instructions are those reachable from address 0, and any that were executed.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Isa};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sub(0x09),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x01]).with_coverage();
+    ///
+    /// cpu.run();
+    /// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+    /// let report = cpu.coverage().unwrap().report(&bytes, Isa::Basic, false);
+    /// assert!(report.contains("Instructions: 4/5 (80.0%)"));
+    /// assert!(report.contains("Branches: 1/2 (50.0%)"));
+    /// assert!(report.contains("0x04  Jpz 0x08  never falls through"));
+    /// assert!(report.contains("0x06  Jmp 0x02  never executed"));
+    /// ```
+    pub fn report(&self, bytes: &[u8], isa: Isa, interrupts: bool) -> String {
+        let instructions = self.instructions(bytes, isa, interrupts);
+        let executed = instructions.iter().filter(|(adr, _)| self.executions(*adr) > 0).count();
+        let branches = instructions.iter().filter(|(_, instruction)| is_branch(instruction)).count() * 2;
+        let covered = instructions.iter()
+            .filter(|(_, instruction)| is_branch(instruction))
+            .map(|(adr, _)| (self.taken(*adr) > 0) as usize + (self.not_taken(*adr) > 0) as usize)
+            .sum::<usize>();
+
+        let mut report = format!("Instructions: {}\nBranches: {}\n", ratio(executed, instructions.len()), ratio(covered, branches));
+        for (adr, instruction) in &instructions {
+            let address = format!("{:#04x}", adr);
+            let text = instruction.to_string();
+            let mut missing = |what: &str| report.push_str(&format!("  {}  {:<8}  {}\n", address, text, what));
+
+            if self.executions(*adr) == 0 {
+                missing("never executed");
+            } else if is_branch(instruction) {
+                if self.taken(*adr) == 0 {
+                    missing("never jumps");
+                }
+                if self.not_taken(*adr) == 0 {
+                    missing("never falls through");
+                }
+            }
+        }
+
+        report
+    }
+
+    /// The program in `bytes` disassembled with `isa`, with how often each instruction was executed, or `#####`
+    /// if it never was, and how often each conditional jump jumped and fell through. Data is shown 16
+    /// bytes to a line.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Isa};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sub(0x09),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x01]).with_coverage();
+    ///
+    /// cpu.run();
+    /// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+    /// let annotated = cpu.coverage().unwrap().annotate(&bytes, Isa::Basic, false);
+    /// assert!(annotated.contains("     1  0x04: Jpz 0x08  jumped 1, fell through 0"));
+    /// assert!(annotated.contains(" #####  0x06: Jmp 0x02"));
+    /// assert!(annotated.contains("        0x09: Var 0x01"));
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![I::hlt()], (0..20).collect()).with_coverage();
+    ///
+    /// cpu.run();
+    /// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+    /// let annotated = cpu.coverage().unwrap().annotate(&bytes, Isa::Basic, false);
+    /// assert!(annotated.contains("        0x01: Data 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n"));
+    /// assert!(annotated.contains("        0x11: Data 10 11 12 13\n"));
+    /// ```
+    pub fn annotate(&self, bytes: &[u8], isa: Isa, interrupts: bool) -> String {
+        let mut annotated = String::new();
+
+        for segment in disassemble_from(bytes, &self.executed(), isa, interrupts) {
+            let line = match &segment {
+                Disassembly::Instruction { instruction, offset, .. } => {
+                    let count = match self.executions(*offset) {
+                        0 => String::from("#####"),
+                        count => count.to_string(),
+                    };
+                    let line = format!("{:>6}  {:#04x}: {}", count, offset, instruction);
+
+                    if is_branch(instruction) {
+                        format!("{}  jumped {}, fell through {}", line, self.taken(*offset), self.not_taken(*offset))
+                    } else {
+                        line
+                    }
+                },
+                Disassembly::Variable { value, offset } => format!("        {:#04x}: Var {:#04x}", offset, value),
+                Disassembly::Data { data, offset, .. } => data.chunks(16).enumerate().map(|(i, chunk)| {
+                    let chunk = chunk.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>();
+                    format!("        {:#04x}: Data {}", *offset as usize + i * 16, chunk.join(" "))
+                }).collect::<Vec<_>>().join("\n"),
+            };
+
+            annotated.push_str(line.trim_end());
+            annotated.push('\n');
+        }
+
+        annotated
+    }
+
+    /// An LCOV tracefile for the program assembled for `isa`, read from `path`: how often each
+    /// source line's instructions were executed, and each conditional jump as a branch that jumps
+    /// or falls through. Lines hold the most executions of their instructions.
+    /// ```
+    /// use busyboard::eater::{assemble, Cpu, Isa};
+    /// let assembly = assemble("
+    ///     ldi 1
+    /// loop:
+    ///     sub one
+    ///     jpz end
+    ///     jmp loop
+    /// end:
+    ///     hlt
+    /// one: db 1
+    /// ", Isa::Basic, false).unwrap();
+    /// let mut cpu = Cpu::from_asm(vec![], vec![]).with_coverage();
+    /// cpu.load(assembly.bytes());
+    ///
+    /// cpu.run();
+    /// let lcov = cpu.coverage().unwrap().lcov(&assembly, Isa::Basic, false, "count.asm");
+    /// assert!(lcov.starts_with("TN:\nSF:count.asm\n"));
+    /// assert!(lcov.contains("DA:5,1\nDA:6,0\n"));
+    /// assert!(lcov.contains("BRDA:5,4,0,1\nBRDA:5,4,1,0\n"));
+    /// assert!(lcov.ends_with("LF:5\nLH:4\nBRF:2\nBRH:1\nend_of_record\n"));
+    /// ```
+    pub fn lcov(&self, assembly: &Assembly, isa: Isa, interrupts: bool, path: &str) -> String {
+        let mut lines = BTreeMap::new();
+        let mut branches = vec![];
+
+        for (adr, instruction) in self.instructions(assembly.bytes(), isa, interrupts) {
+            let Some(line) = assembly.line(adr).map(|line| line + 1) else { continue };
+            let count = lines.entry(line).or_insert(0);
+            *count = self.executions(adr).max(*count);
+
+            if is_branch(&instruction) {
+                branches.push((line, adr));
+            }
+        }
+
+        let mut lcov = format!("TN:\nSF:{}\n", path);
+        for (line, count) in &lines {
+            lcov.push_str(&format!("DA:{},{}\n", line, count));
+        }
+        for (line, adr) in &branches {
+            let count = |count: u64| if self.executions(*adr) == 0 { String::from("-") } else { count.to_string() };
+            lcov.push_str(&format!("BRDA:{},{},0,{}\n", line, adr, count(self.taken(*adr))));
+            lcov.push_str(&format!("BRDA:{},{},1,{}\n", line, adr, count(self.not_taken(*adr))));
+        }
+
+        let hit = lines.values().filter(|count| **count > 0).count();
+        let branches_hit = branches.iter()
+            .map(|(_, adr)| (self.taken(*adr) > 0) as usize + (self.not_taken(*adr) > 0) as usize)
+            .sum::<usize>();
+        lcov.push_str(&format!("LF:{}\nLH:{}\n", lines.len(), hit));
+        lcov.push_str(&format!("BRF:{}\nBRH:{}\nend_of_record\n", branches.len() * 2, branches_hit));
+
+        lcov
+    }
+
+    pub (super) fn execute(&mut self, adr: u8, taken: Option<bool>) {
+        self.executions[adr as usize] += 1;
+
+        match taken {
+            Some(true) => self.taken[adr as usize] += 1,
+            Some(false) => self.not_taken[adr as usize] += 1,
+            None => {},
+        }
+    }
+
+    /// Returns the addresses of the instructions executed.
+    fn executed(&self) -> Vec<u8> {
+        (0..=255).filter(|adr| self.executions(*adr) > 0).collect()
+    }
+
+    /// Returns the program's instructions with their addresses, in address order.
+    fn instructions(&self, bytes: &[u8], isa: Isa, interrupts: bool) -> Vec<(u8, I)> {
+        disassemble_from(bytes, &self.executed(), isa, interrupts).into_iter().filter_map(|segment| match segment {
+            Disassembly::Instruction { instruction, offset, .. } => Some((offset, instruction)),
+            _ => None,
+        }).collect()
+    }
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self { executions: [0; 256], taken: [0; 256], not_taken: [0; 256] }
+    }
+}
+
+fn is_branch(instruction: &I) -> bool {
+    matches!(instruction, I::Jpz(..) | I::Jpc(..))
+}
+
+fn ratio(covered: usize, total: usize) -> String {
+    let percent = if total == 0 { 100.0 } else { covered as f64 * 100.0 / total as f64 };
+    format!("{}/{} ({:.1}%)", covered, total, percent)
+}
@@ -1,17 +1,48 @@
-use super::{I, IBuilder, Instruction};
+use super::{Access, AccessMap, Coverage, Device, I, IBuilder, Instruction, Interrupts, Isa, Loop, LoopDetector, MemoryMap, Policy, Profile, Violation};
+use std::ops::RangeInclusive;
 
 pub enum Flag {
     Carry = 0,
     Halt = 1,
     IllegalHalt = 2,
+    InterruptEnable = 3,
+}
+
+/// The reason `Cpu::run` returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halt,
+    IllegalHalt,
+    Loop(Loop),
+    Fault(Violation),
 }
 
 pub struct Cpu {
     pub (super) a: u8,
+    pub (super) b: u8,
+    pub (super) bus: u8,
     pub (super) ip: u8,
+    pub (super) sp: u8,
+    pub (super) ir: u8,
+    pub (super) mar: u8,
     pub (super) flags: u8,
+    pub (super) cycles: u64,
+    pub (super) instructions: u64,
     pub (super) ram: Vec<u8>,
+    pub (super) initialized: [bool; 256],
+    pub (super) memory: MemoryMap,
+    pub (super) fault: Option<Violation>,
+    pub (super) warnings: Vec<Violation>,
+    pub (super) devices: Vec<(RangeInclusive<u8>, Box<dyn Device>)>,
+    pub (super) input: Box<dyn FnMut() -> u8>,
     pub (super) out: Box<dyn FnMut(u8)>,
+    pub (super) accesses: Option<AccessMap>,
+    pub (super) profile: Option<Profile>,
+    pub (super) coverage: Option<Coverage>,
+    pub (super) interrupts: Option<Interrupts>,
+    pub (super) external_irq: bool,
+    pub (super) isa: Isa,
+    pub (super) detector: Option<LoopDetector>,
 }
 
 impl Cpu {
@@ -23,17 +54,54 @@ impl Cpu {
     /// ], vec![ ]);
     /// ```
     pub fn from_asm(asm: Vec<I>, data: Vec<u8>) -> Self {
-        let ram = asm.into_iter().flat_map(|i| i.assemble()).chain(data).collect();
+        let ram = asm.into_iter().flat_map(|i| i.assemble()).chain(data).collect::<Vec<_>>();
+        let mut initialized = [false; 256];
+        initialized[..ram.len().min(256)].fill(true);
 
         Cpu {
             a: 0,
+            b: 0,
+            bus: 0,
             ip: 0,
+            sp: 0,
+            ir: 0,
+            mar: 0,
             flags: 0,
+            cycles: 0,
+            instructions: 0,
             ram,
+            initialized,
+            memory: MemoryMap::default(),
+            fault: None,
+            warnings: vec![],
+            devices: vec![],
+            input: Box::from(default_in),
             out: Box::from(default_out),
+            accesses: None,
+            profile: None,
+            coverage: None,
+            interrupts: None,
+            external_irq: false,
+            isa: Isa::Basic,
+            detector: None,
         }
     }
 
+    /// Returns the recorded RAM accesses, if recording was enabled with `with_access_map`.
+    pub fn access_map(&self) -> Option<&AccessMap> {
+        self.accesses.as_ref()
+    }
+
+    /// Returns the instructions and branches executed, if enabled with `with_coverage`.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Returns the counts recorded while executing, if profiling was enabled with `with_profiler`.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
     /// Returns the contents of the A register.
     /// ```
     /// use busyboard::eater::{Cpu, Flag, I};
@@ -49,6 +117,102 @@ impl Cpu {
         self.a
     }
 
+    /// Returns the contents of the B register, the operand of the last `Add` or `Sub`.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::add(0x03),
+    ///     I::hlt()
+    /// ], vec![0x2a]);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.b(), 0x2a);
+    /// ```
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    /// Returns the last value transferred over the bus.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sta(0x06),
+    ///     I::hlt()
+    /// ], vec![]);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.bus(), 0x01);
+    /// ```
+    pub fn bus(&self) -> u8 {
+        self.bus
+    }
+
+    /// Returns the clock cycles, or T-states, taken by the instructions executed since the program
+    /// was loaded. Each takes 2 to fetch its opcode and 2 more to fetch an operand, then a cycle per
+    /// microcode step:
+    ///
+    /// | Instruction | Cycles |
+    /// |---|---|
+    /// | `Nop` | 2 |
+    /// | `Ldi`, `Jmp` | 4 |
+    /// | `Lda`, `Sta`, `Ret`, `Push`, `Pop` | 5 |
+    /// | `Add`, `Sub` | 6 |
+    /// | `Call` | 8 |
+    /// | `Jpz`, `Jpc` | 4, or 3 when they don't jump |
+    /// | `In`, `Out`, `Hlt`, `Rti`, `Ei`, `Di` | 3 |
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::jpz(0x00),
+    ///     I::add(0x07),
+    ///     I::hlt(),
+    /// ], vec![0x02]);
+    ///
+    /// cpu.run();
+    /// assert_eq!(cpu.instructions(), 4);
+    /// assert_eq!(cpu.cycles(), 4 + 3 + 6 + 3);
+    /// ```
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Returns the write that halted the CPU, if the memory map's policy for it is to fault.
+    pub fn fault(&self) -> Option<Violation> {
+        self.fault
+    }
+
+    /// Returns true if the byte at the given address was loaded with the program or written since.
+    pub fn is_initialized(&self, adr: u8) -> bool {
+        self.initialized[adr as usize]
+    }
+
+    /// Returns the instructions the CPU decodes.
+    pub fn isa(&self) -> Isa {
+        self.isa
+    }
+
+    /// Returns the stack pointer: the address of the top of the stack, which starts empty at the end
+    /// of memory, wrapping to 0 when memory is 256 bytes. Only used by the extended instructions.
+    pub fn sp(&self) -> u8 {
+        self.sp
+    }
+
+    /// Returns the interrupt line, if enabled with `with_interrupts`.
+    pub fn interrupts(&self) -> Option<&Interrupts> {
+        self.interrupts.as_ref()
+    }
+
+    /// Raise an interrupt request, which is handled once `Flag::InterruptEnable` is set. Does
+    /// nothing without interrupts.
+    pub fn irq(&mut self) {
+        if let Some(interrupts) = self.interrupts.as_mut() {
+            interrupts.requested = true;
+            self.reset_loop_detection();
+        }
+    }
+
     /// Returns the value of the given flag.
     /// ```
     /// use busyboard::eater::{Cpu, Flag, I};
@@ -76,6 +240,7 @@ impl Cpu {
     /// ```
     pub fn goto(&mut self, ip: u8) {
         self.ip = ip;
+        self.reset_loop_detection();
     }
 
     /// Returns the contents of the instruction pointer.
@@ -93,11 +258,128 @@ impl Cpu {
         self.ip
     }
 
+    /// Returns the contents of the instruction register, the opcode of the last instruction fetched.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::out(),
+    ///     I::hlt()
+    /// ], vec![]).with_out(|_| {});
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.ir(), 14);
+    /// ```
+    pub fn ir(&self) -> u8 {
+        self.ir
+    }
+
+    /// Returns the number of instructions executed since the program was loaded.
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Returns the contents of the memory address register, the last address read or written.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sta(0x06),
+    ///     I::hlt()
+    /// ], vec![]);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.mar(), 0x01);
+    /// cpu.step();
+    /// assert_eq!(cpu.mar(), 0x06);
+    /// ```
+    pub fn mar(&self) -> u8 {
+        self.mar
+    }
+
+    /// Returns the memory map, which `with_memory_map` sets.
+    pub fn memory_map(&self) -> &MemoryMap {
+        &self.memory
+    }
+
+    /// Returns the loop the CPU is stuck in, if loop detection was enabled with `with_loop_detection`.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::nop(),
+    ///     I::jmp(0x00),
+    /// ], vec![]).with_loop_detection();
+    ///
+    /// for _ in 0..4 {
+    ///     cpu.step();
+    /// }
+    ///
+    /// let detected = cpu.non_terminating().unwrap();
+    /// assert_eq!(detected.to_string(), "non-terminating loop between step 1 and 3");
+    /// ```
+    pub fn non_terminating(&self) -> Option<Loop> {
+        self.detector.as_ref().and_then(|detector| detector.detected())
+    }
+
+    /// Replace the RAM with the given bytes, cut or filled to the size of memory, and reset the
+    /// registers and flags, as if the CPU had just been created with them.
+    /// ```
+    /// use busyboard::eater::{Cpu, Flag, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::hlt(),
+    /// ], vec![]);
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// cpu.load(&[0x0e, 0x0f]);
+    /// assert_eq!(cpu.a(), 0);
+    /// assert_eq!(cpu.ip(), 0);
+    /// assert!(!cpu.get(Flag::Halt));
+    /// assert_eq!(cpu.len(), 2);
+    /// ```
+    pub fn load(&mut self, bytes: &[u8]) {
+        self.a = 0;
+        self.b = 0;
+        self.bus = 0;
+        self.ip = 0;
+        self.sp = self.memory.size() as u8;
+        self.ir = 0;
+        self.mar = 0;
+        self.flags = 0;
+        self.cycles = 0;
+        self.instructions = 0;
+        self.fault = None;
+        self.warnings.clear();
+        self.ram = bytes.to_vec();
+        self.initialized = [false; 256];
+        self.initialized[..bytes.len().min(self.memory.size())].fill(true);
+        self.memory.pad(&mut self.ram);
+
+        if let Some(accesses) = self.accesses.as_mut() {
+            accesses.clear();
+        }
+        if let Some(profile) = self.profile.as_mut() {
+            profile.clear();
+        }
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.clear();
+        }
+        if let Some(interrupts) = self.interrupts.as_mut() {
+            *interrupts = Interrupts::new(interrupts.vector);
+        }
+        self.reset_loop_detection();
+    }
+
     /// Returns the number of bytes in the RAM.
     pub fn len(&self) -> usize {
         self.ram.len()
     }
 
+    /// Returns true if the RAM contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.ram.is_empty()
+    }
+
     /// Read the value at the given address in RAM.
     /// ```
     /// use busyboard::eater::{Cpu, Flag, I};
@@ -115,13 +397,87 @@ impl Cpu {
         Some(self.ram[adr as usize])
     }
 
-    pub fn read_bytes(&self, adr: u8, len: u8) -> &[u8] {
-        let start = adr as usize;
-        let end = (adr as usize + len as usize).min(self.ram.len());
+    /// Returns up to `len` bytes of RAM starting at the given address.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![], vec![]);
+    /// cpu.write(0xff, 0x2a);
+    ///
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()).len(), 256);
+    /// assert_eq!(cpu.read_bytes(0xfe, 4), [0x00, 0x2a]);
+    /// ```
+    pub fn read_bytes(&self, adr: u8, len: usize) -> &[u8] {
+        let start = (adr as usize).min(self.ram.len());
+        let end = (adr as usize + len).min(self.ram.len());
 
         &self.ram[start..end]
     }
 
+    pub (super) fn record(&mut self, adr: u8, access: Access) {
+        if let Some(accesses) = self.accesses.as_mut() {
+            accesses.record(adr, access);
+        }
+        if let Some(profile) = self.profile.as_mut() {
+            profile.record(adr, access);
+        }
+    }
+
+    /// Respond to an access the memory map forbids as its policy says.
+    fn report(&mut self, violation: Violation, policy: Policy) {
+        match policy {
+            Policy::Ignore => {},
+            Policy::Warn if self.warnings.contains(&violation) => {},
+            Policy::Warn => self.warnings.push(violation),
+            Policy::Fault => {
+                self.fault = Some(violation);
+                self.set(Flag::IllegalHalt);
+            },
+        }
+    }
+
+    /// Returns true if an interrupt can be requested at any time, from outside or by a device.
+    fn interruptible(&self) -> bool {
+        self.external_irq || self.devices.iter().any(|(_, device)| device.can_interrupt())
+    }
+
+    pub (super) fn reset_loop_detection(&mut self) {
+        if self.detector.is_some() {
+            self.detector = Some(LoopDetector::new(self));
+        }
+    }
+
+    /// Execute instructions until the CPU halts or, if loop detection is enabled, a loop is detected.
+    /// Without loop detection, a program that never halts runs forever.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Loop, Stop};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x03),
+    ///     I::hlt(),
+    /// ], vec![]);
+    /// assert_eq!(cpu.run(), Stop::Halt);
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x03),
+    ///     I::jmp(0x02),
+    /// ], vec![]).with_loop_detection();
+    /// assert_eq!(cpu.run(), Stop::Loop(Loop { start: 1, end: 2 }));
+    /// ```
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if self.get(Flag::Halt) {
+                return Stop::Halt;
+            } else if let Some(violation) = self.fault {
+                return Stop::Fault(violation);
+            } else if self.get(Flag::IllegalHalt) {
+                return Stop::IllegalHalt;
+            } else if let Some(detected) = self.non_terminating() {
+                return Stop::Loop(detected);
+            }
+
+            self.step();
+        }
+    }
+
     pub (super) fn set(&mut self, flag: Flag) {
        self.flags |= 1 << flag as u8;
     }
@@ -147,22 +503,202 @@ impl Cpu {
 
         if let Some(instruction) = decode(self) {
             instruction.execute(self);
+            let cycles = instruction.cycles(self);
+            let taken = instruction.taken(self);
+            self.cycles += cycles as u64;
+            self.instructions += 1;
+
+            if let Some(profile) = self.profile.as_mut() {
+                profile.execute(self.ip, cycles);
+            }
+            if let Some(coverage) = self.coverage.as_mut() {
+                coverage.execute(self.ip, taken);
+            }
 
             if self.get(Flag::Halt) || self.get(Flag::IllegalHalt) {
                 return;
             }
 
+            for (_, device) in &mut self.devices {
+                device.tick();
+            }
+
             let next_ip = instruction.next(self);
             if next_ip as usize >= self.ram.len() {
                 self.set(Flag::IllegalHalt);
             } else {
                 self.ip = next_ip;
             }
+
+            interrupt(self);
+
+            // While interrupts are enabled, a device or key may request one at any time, so nothing repeats
+            if !self.get(Flag::InterruptEnable) || !self.interruptible() {
+                if let Some(mut detector) = self.detector.take() {
+                    detector.observe(self);
+                    self.detector = Some(detector);
+                }
+            }
         } else {
             self.set(Flag::IllegalHalt);
         }
     }
 
+    /// Record how each address is accessed while executing.
+    /// ```
+    /// use busyboard::eater::{Access, Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sta(0x05),
+    /// ], vec![]).with_access_map();
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// assert!(cpu.access_map().unwrap().get(0x05, Access::Write));
+    /// ```
+    pub fn with_access_map(mut self) -> Self {
+        self.accesses = Some(AccessMap::default());
+        self
+    }
+
+    /// Record which instructions execute and which way each conditional jump goes. Loading a
+    /// program starts over, so use `Coverage::merge` to combine runs.
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = Some(Coverage::default());
+        self
+    }
+
+    /// Decode the given instruction set.
+    pub fn with_isa(mut self, isa: Isa) -> Self {
+        self.isa = isa;
+        self
+    }
+
+    /// Handle interrupt requests by jumping to the vector. `Rti`, `Ei` and `Di` are illegal without.
+    /// While interrupts are enabled, loops are only detected if neither an attached device nor
+    /// `with_external_irq` can request one.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Interrupts, Timer};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ei(),
+    ///     I::jmp(0x01),
+    /// ], vec![]).with_interrupts(Interrupts::new(0x80)).with_loop_detection();
+    ///
+    /// for _ in 0..8 {
+    ///     cpu.step();
+    /// }
+    /// assert!(cpu.non_terminating().is_some());
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ei(),
+    ///     I::jmp(0x01),
+    /// ], vec![]).with_interrupts(Interrupts::new(0x80)).with_device(0xf0..=0xf1, Timer::new()).with_loop_detection();
+    ///
+    /// for _ in 0..8 {
+    ///     cpu.step();
+    /// }
+    /// assert!(cpu.non_terminating().is_none());
+    /// ```
+    pub fn with_interrupts(mut self, interrupts: Interrupts) -> Self {
+        self.interrupts = Some(interrupts);
+        self
+    }
+
+    /// Expect `irq` to be called at any time, as from a key, so loops aren't detected while
+    /// interrupts are enabled.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Interrupts};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ei(),
+    ///     I::jmp(0x01),
+    /// ], vec![]).with_interrupts(Interrupts::new(0x80)).with_external_irq().with_loop_detection();
+    ///
+    /// for _ in 0..8 {
+    ///     cpu.step();
+    /// }
+    /// assert!(cpu.non_terminating().is_none());
+    /// ```
+    pub fn with_external_irq(mut self) -> Self {
+        self.external_irq = true;
+        self
+    }
+
+    /// Size and fill memory and protect read-only regions from `Sta`, as the memory map says. Bytes
+    /// past the end are dropped.
+    pub fn with_memory_map(mut self, memory: MemoryMap) -> Self {
+        memory.pad(&mut self.ram);
+        self.sp = memory.size() as u8;
+        self.memory = memory;
+        self.reset_loop_detection();
+        self
+    }
+
+    /// Count executions, cycles, reads and writes per address while executing.
+    pub fn with_profiler(mut self) -> Self {
+        self.profile = Some(Profile::default());
+        self
+    }
+
+    /// Detect when the CPU revisits a prior state, which means the program never halts.
+    /// Editing the RAM or IP with `write` or `goto` restarts detection.
+    pub fn with_loop_detection(mut self) -> Self {
+        self.detector = Some(LoopDetector::new(&self));
+        self
+    }
+
+    /// Attach a device in place of RAM at the given addresses, which `Lda`, `Add`, `Sub` and `Sta`
+    /// then reach instead. `read` still returns the RAM underneath. Where ranges overlap, the device
+    /// attached first wins.
+    /// ```
+    /// use busyboard::eater::{Cpu, Device, I};
+    ///
+    /// struct Doubler(u8);
+    /// impl Device for Doubler {
+    ///     fn read(&mut self, _offset: u8) -> u8 { self.0 * 2 }
+    ///     fn write(&mut self, _offset: u8, value: u8) { self.0 = value }
+    /// }
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x15),
+    ///     I::sta(0x80),
+    ///     I::lda(0x80),
+    ///     I::hlt(),
+    /// ], vec![]).with_device(0x80..=0x80, Doubler(0));
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 0x2a);
+    /// assert_eq!(cpu.len(), 7);
+    /// ```
+    pub fn with_device<D>(mut self, range: RangeInclusive<u8>, device: D) -> Self
+    where
+        D: Device + 'static,
+    {
+        self.devices.push((range, Box::from(device)));
+        self
+    }
+
+    /// Use the given function to provide input, which otherwise reads as 0.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::input(),
+    ///     I::out(),
+    ///     I::hlt(),
+    /// ], vec![]).with_in(|| 0x11);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 0x11);
+    /// ```
+    pub fn with_in<F>(mut self, input: F) -> Self
+    where
+        F: FnMut() -> u8 + 'static,
+    {
+        self.input = Box::from(input);
+        self
+    }
+
     /// Use the given function to handle output.
     /// ```
     /// use busyboard::eater::{Cpu, Flag, I};
@@ -189,11 +725,18 @@ impl Cpu {
         self
     }
 
+    /// Returns each access the memory map forbids that happened since the program was loaded, once,
+    /// if the policy for it is to warn.
+    pub fn warnings(&self) -> &[Violation] {
+        &self.warnings
+    }
+
     pub (super) fn unset(&mut self, flag: Flag) {
        self.flags &= !(1 << flag as u8);
     }
 
-    /// Write the given value to the given address in RAM.
+    /// Write the given value to the given address in RAM, unless it is past the end of memory.
+    /// Read-only regions are written too.
     /// ```
     /// use busyboard::eater::{Cpu, Flag, I};
     /// let mut cpu = Cpu::from_asm(vec![
@@ -208,31 +751,140 @@ impl Cpu {
     /// assert_eq!(cpu.read(1), Some(0x96));
     /// assert_eq!(cpu.a(), 0x96);
     pub fn write(&mut self, adr: u8, val: u8) {
+        if adr as usize >= self.memory.size() {
+            return;
+        }
+
+        self.store(adr, val);
+        self.reset_loop_detection();
+    }
+
+    /// Shorten the RAM to the given number of bytes, as if the bytes past it were never written.
+    /// Does nothing if it is no longer.
+    /// ```
+    /// use busyboard::eater::Cpu;
+    /// let mut cpu = Cpu::from_asm(vec![], vec![0x2a]);
+    /// cpu.write(3, 0x01);
+    /// assert_eq!(cpu.len(), 4);
+    ///
+    /// cpu.truncate(1);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x2a]);
+    /// assert!(!cpu.is_initialized(3));
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.ram.len() {
+            self.initialized[len..self.ram.len()].fill(false);
+            self.ram.truncate(len);
+            self.reset_loop_detection();
+        }
+    }
+
+    /// Read the byte at the given address for an instruction, from the device there if any. Returns
+    /// `None` if the byte was never set and the memory map's policy for it is to fault.
+    pub (super) fn load_byte(&mut self, adr: u8) -> Option<u8> {
+        match self.devices.iter_mut().find(|(range, _)| range.contains(&adr)) {
+            Some((range, device)) => {
+                let value = device.read(adr - range.start());
+
+                // A device can return something else next time, so a repeated state is no loop
+                self.reset_loop_detection();
+                Some(value)
+            },
+            None => {
+                if let Some((violation, policy)) = self.memory.check_read(adr, self.is_initialized(adr)) {
+                    self.report(violation, policy);
+
+                    if policy == Policy::Fault {
+                        return None;
+                    }
+                }
+
+                self.read(adr)
+            },
+        }
+    }
+
+    /// Write the byte at the given address for an instruction, to the device there if any, unless
+    /// the memory map forbids it. Only writes that happen are recorded.
+    pub (super) fn store_byte(&mut self, adr: u8, val: u8) {
+        if let Some((range, device)) = self.devices.iter_mut().find(|(range, _)| range.contains(&adr)) {
+            device.write(adr - range.start(), val);
+            return self.record(adr, Access::Write);
+        }
+
+        match self.memory.check_write(adr) {
+            None => {
+                self.store(adr, val);
+                self.record(adr, Access::Write);
+            },
+            Some((violation, policy)) => self.report(violation, policy),
+        }
+    }
+
+    pub (super) fn store(&mut self, adr: u8, val: u8) {
         if adr as usize >= self.ram.len() {
             let padding = vec![0; adr as usize - self.ram.len() + 1];
             self.ram.extend(padding);
         }
 
         self.ram[adr as usize] = val;
+        self.initialized[adr as usize] = true;
+    }
+}
+
+/// Jump to the interrupt handler, saving the IP, if interrupts are enabled and a request was raised
+/// or a device is requesting one.
+fn interrupt(cpu: &mut Cpu) {
+    if cpu.interrupts.is_none() || !cpu.get(Flag::InterruptEnable) || cpu.get(Flag::Halt) || cpu.get(Flag::IllegalHalt) {
+        return;
+    }
+
+    let device = cpu.devices.iter_mut().fold(false, |requested, (_, device)| device.irq() || requested);
+    if let Some(interrupts) = cpu.interrupts.as_mut().filter(|interrupts| interrupts.requested || device) {
+        interrupts.requested = false;
+        interrupts.saved = cpu.ip;
+        cpu.ip = interrupts.vector;
+        cpu.unset(Flag::InterruptEnable);
+        cpu.reset_loop_detection();
     }
 }
 
 fn decode(cpu: &mut Cpu) -> Option<I> {
     if let Some(opcode) = cpu.read(cpu.ip) {
-        return match I::from_opcode(opcode) {
-            IBuilder::Complete(instruction) => return Some(instruction),
-            IBuilder::NeedsData(incomplete) => if let Some(id) = cpu.read(cpu.ip + 1) {
-                Some(incomplete.with_data(id))
-            } else {
-                None
-            }
-            _ => None
-        }
+        cpu.record(cpu.ip, Access::Opcode);
+        cpu.mar = cpu.ip;
+        cpu.bus = opcode;
+        cpu.ir = opcode;
+
+        let instruction = match I::from_opcode(opcode) {
+            IBuilder::Complete(instruction) => Some(instruction),
+            IBuilder::NeedsData(incomplete) => cpu.read(cpu.ip.wrapping_add(1)).map(|id| {
+                cpu.record(cpu.ip.wrapping_add(1), Access::Operand);
+                cpu.mar = cpu.ip.wrapping_add(1);
+                cpu.bus = id;
+                incomplete.with_data(id)
+            }),
+            _ => None,
+        };
+
+        return instruction.filter(|instruction| supports(cpu.isa, cpu.interrupts.is_some(), instruction));
     }
 
     None
 }
 
+/// Returns false for the instructions of features the CPU doesn't have, which are illegal.
+pub (super) fn supports(isa: Isa, interrupts: bool, instruction: &I) -> bool {
+    match instruction {
+        I::Rti(..) | I::Ei(..) | I::Di(..) => interrupts,
+        _ => isa.supports(instruction),
+    }
+}
+
+fn default_in() -> u8 {
+    0
+}
+
 fn default_out(value: u8) {
     print!("{} ", value);
 }
@@ -0,0 +1,59 @@
+use super::{Cpu, State};
+
+/// A non-terminating loop: the machine state after `end` steps equals the state after `start` steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Loop {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Detects when the CPU revisits a prior state using Brent's cycle detection algorithm.
+///
+/// Only one snapshot of the machine is kept at a time. The snapshot is replaced each time the
+/// number of steps since it was taken reaches a power of two, so a loop is reported at most two
+/// periods after it was entered.
+pub (super) struct LoopDetector {
+    snapshot: State,
+    snapshot_step: u64,
+    step: u64,
+    power: u64,
+    detected: Option<Loop>,
+}
+
+impl LoopDetector {
+    pub (super) fn new(cpu: &Cpu) -> Self {
+        Self {
+            snapshot: State::of(cpu),
+            snapshot_step: 0,
+            step: 0,
+            power: 1,
+            detected: None,
+        }
+    }
+
+    pub (super) fn detected(&self) -> Option<Loop> {
+        self.detected
+    }
+
+    pub (super) fn observe(&mut self, cpu: &Cpu) {
+        if self.detected.is_some() {
+            return;
+        }
+
+        self.step += 1;
+
+        if self.snapshot.matches(cpu) {
+            self.detected = Some(Loop { start: self.snapshot_step, end: self.step });
+        } else if self.step - self.snapshot_step == self.power {
+            self.snapshot = State::of(cpu);
+            self.snapshot_step = self.step;
+            self.power *= 2;
+        }
+    }
+}
+
+impl std::fmt::Display for Loop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "non-terminating loop between step {} and {}", self.start, self.end)
+    }
+}
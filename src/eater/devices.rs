@@ -0,0 +1,266 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Something attached to the bus in place of RAM for a range of addresses. `Lda`, `Add` and `Sub`
+/// read from it and `Sta` writes to it, at an offset from the start of its range.
+pub trait Device {
+    fn read(&mut self, offset: u8) -> u8;
+    fn write(&mut self, offset: u8, value: u8);
+
+    /// Called once after each instruction executes.
+    fn tick(&mut self) {}
+
+    /// Returns true while the device requests an interrupt. Only asked while interrupts are enabled.
+    fn irq(&mut self) -> bool {
+        false
+    }
+
+    /// Returns true if the device may ever request an interrupt, which keeps the CPU from detecting
+    /// loops while interrupts are enabled.
+    fn can_interrupt(&self) -> bool {
+        false
+    }
+}
+
+/// A serial console. Offset 0 reads the next byte received, or 0 when there is none, and writes a
+/// byte to send. Offset 1 reads the status: bit 0 is set when a byte has been received. It requests
+/// an interrupt while a received byte waits to be read.
+pub struct Uart {
+    receive: Box<dyn FnMut() -> Option<u8>>,
+    send: Box<dyn FnMut(u8)>,
+    received: Option<u8>,
+}
+
+/// Counts instructions. Offset 0 reads the count, which wraps at 256, and writing it sets the
+/// count. Offset 1 reads 1 if the count wrapped since it was last read, and 0 otherwise. It requests
+/// an interrupt from when the count wraps until offset 1 is read.
+#[derive(Default)]
+pub struct Timer {
+    count: u8,
+    wrapped: bool,
+}
+
+/// A 2 line by 16 character display. Writing to offset 0 sends a command: `0x01` clears the
+/// display and `0x80 | position` moves the cursor, where the second line starts at `0x40`. Writing
+/// to offset 1 shows a character at the cursor and moves it right. Reading offset 0 returns the
+/// cursor position. Clones share the same display, so one can be attached while another shows it.
+#[derive(Clone, Default)]
+pub struct Lcd(Rc<RefCell<Screen>>);
+
+#[derive(Default)]
+struct Screen {
+    lines: [[u8; Lcd::WIDTH]; 2],
+    cursor: u8,
+}
+
+/// A source of pseudo-random bytes. Reading returns the next byte and writing reseeds it.
+pub struct Rng {
+    state: u8,
+}
+
+impl Uart {
+    /// A console that receives bytes from `receive`, until it returns `None`, and sends them to
+    /// `send`.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Uart};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let sent = Rc::new(RefCell::new(vec![]));
+    /// let s = sent.clone();
+    /// let mut received = "hi".bytes();
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0xf0),
+    ///     I::sta(0xf0),
+    ///     I::lda(0xf1),
+    ///     I::hlt(),
+    /// ], vec![]).with_device(0xf0..=0xf1, Uart::new(move || received.next(), move |byte| s.borrow_mut().push(byte)));
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), b'h');
+    /// cpu.step();
+    /// assert_eq!(*sent.borrow(), b"h");
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 1);
+    /// ```
+    pub fn new<R, S>(receive: R, send: S) -> Self
+    where
+        R: FnMut() -> Option<u8> + 'static,
+        S: FnMut(u8) + 'static,
+    {
+        Self { receive: Box::from(receive), send: Box::from(send), received: None }
+    }
+
+    fn poll(&mut self) -> Option<u8> {
+        if self.received.is_none() {
+            self.received = (self.receive)();
+        }
+
+        self.received
+    }
+}
+
+impl Device for Uart {
+    fn read(&mut self, offset: u8) -> u8 {
+        match offset {
+            0 => {
+                let byte = self.poll().unwrap_or(0);
+                self.received = None;
+                byte
+            },
+            _ => self.poll().is_some() as u8,
+        }
+    }
+
+    fn write(&mut self, offset: u8, value: u8) {
+        if offset == 0 {
+            (self.send)(value);
+        }
+    }
+
+    fn irq(&mut self) -> bool {
+        self.poll().is_some()
+    }
+
+    fn can_interrupt(&self) -> bool {
+        true
+    }
+}
+
+impl Timer {
+    /// A timer starting from 0.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Timer};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::nop(),
+    ///     I::nop(),
+    ///     I::lda(0xe0),
+    ///     I::hlt(),
+    /// ], vec![]).with_device(0xe0..=0xe1, Timer::new());
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 2);
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Device for Timer {
+    fn read(&mut self, offset: u8) -> u8 {
+        match offset {
+            0 => self.count,
+            _ => std::mem::take(&mut self.wrapped) as u8,
+        }
+    }
+
+    fn write(&mut self, offset: u8, value: u8) {
+        if offset == 0 {
+            self.count = value;
+        }
+    }
+
+    fn tick(&mut self) {
+        self.count = self.count.wrapping_add(1);
+        self.wrapped |= self.count == 0;
+    }
+
+    fn irq(&mut self) -> bool {
+        self.wrapped
+    }
+
+    fn can_interrupt(&self) -> bool {
+        true
+    }
+}
+
+impl Lcd {
+    pub const WIDTH: usize = 16;
+
+    /// A blank display.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Lcd};
+    /// let lcd = Lcd::new();
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x0a),
+    ///     I::sta(0xd1),
+    ///     I::hlt(),
+    /// ], vec![]).with_device(0xd0..=0xd1, lcd.clone());
+    /// cpu.write(0x0a, b'A');
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(lcd.lines()[0].trim_end(), "A");
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The text on each line, with a space where nothing was written.
+    pub fn lines(&self) -> [String; 2] {
+        let screen = self.0.borrow();
+        screen.lines.map(|line| line.iter().map(|&c| if c.is_ascii_graphic() { c as char } else { ' ' }).collect())
+    }
+}
+
+impl Device for Lcd {
+    fn read(&mut self, _offset: u8) -> u8 {
+        self.0.borrow().cursor
+    }
+
+    fn write(&mut self, offset: u8, value: u8) {
+        let mut screen = self.0.borrow_mut();
+
+        match (offset, value) {
+            (0, 0x01) => *screen = Screen::default(),
+            (0, value) if value & 0x80 != 0 => screen.cursor = value & 0x7f,
+            (0, _) => {},
+            (_, value) => {
+                let (line, column) = ((screen.cursor >> 6) as usize & 1, (screen.cursor & 0x3f) as usize);
+                if column < Lcd::WIDTH {
+                    screen.lines[line][column] = value;
+                }
+                screen.cursor = screen.cursor.wrapping_add(1) & 0x7f;
+            },
+        }
+    }
+}
+
+impl Rng {
+    /// A generator starting from the given seed, so runs can be repeated.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Rng};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0xc0),
+    ///     I::sta(0x0a),
+    ///     I::lda(0xc0),
+    ///     I::sub(0x0a),
+    ///     I::hlt(),
+    /// ], vec![]).with_device(0xc0..=0xc0, Rng::new(1));
+    ///
+    /// for _ in 0..4 {
+    ///     cpu.step();
+    /// }
+    /// assert_ne!(cpu.a(), 0);
+    /// ```
+    pub fn new(seed: u8) -> Self {
+        Self { state: seed.max(1) }
+    }
+}
+
+impl Device for Rng {
+    fn read(&mut self, _offset: u8) -> u8 {
+        // An 8-bit xorshift, which visits every non-zero value before repeating
+        self.state ^= self.state << 1;
+        self.state ^= self.state >> 1;
+        self.state ^= self.state << 2;
+        self.state
+    }
+
+    fn write(&mut self, _offset: u8, value: u8) {
+        self.state = value.max(1);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{I, IBuilder};
+use super::{Access, AccessMap, I, IBuilder, Isa, supports};
 
 pub enum Disassembly {
     Data {
@@ -11,6 +11,10 @@ pub enum Disassembly {
         len: u8,
         offset: u8,
     },
+    Variable {
+        value: u8,
+        offset: u8,
+    },
 }
 
 impl Disassembly {
@@ -18,6 +22,7 @@ impl Disassembly {
         match self {
             Disassembly::Data { offset, .. } => *offset,
             Disassembly::Instruction { offset, .. } => *offset,
+            Disassembly::Variable { offset, .. } => *offset,
         }
     }
 
@@ -25,59 +30,209 @@ impl Disassembly {
         match self {
             Disassembly::Data { len, .. } => *len,
             Disassembly::Instruction { len, .. } => *len,
+            Disassembly::Variable { .. } => 1,
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
+/// Disassemble the given bytes with the basic instruction set and no interrupts, from address 0.
+/// ```
+/// use busyboard::eater::{disassemble, Disassembly, I};
+/// // Ldi 0; Jmp 5; Hlt; Nop
+/// let disassembly = disassemble(&[0x01, 0x00, 0x06, 0x05, 0x0f, 0x00]);
+///
+/// assert_eq!(disassembly.len(), 4);
+/// assert!(matches!(disassembly[2], Disassembly::Data { offset: 4, len: 1, .. }));
+/// assert!(matches!(disassembly[3], Disassembly::Instruction { instruction: I::Nop(..), offset: 5, .. }));
+/// ```
 pub fn disassemble(bytes: &[u8]) -> Vec<Disassembly> {
-    let mut disassembly = vec![
-        Disassembly::Data { data: bytes.to_vec(), len: bytes.len() as u8, offset: 0 }
-    ];
+    disassemble_from(bytes, &[], Isa::Basic, false)
+}
+
+/// Disassemble the given bytes, starting at address 0 and at each of the given entry points.
+///
+/// Decoding follows the control flow of the program: `Hlt`, `Jmp`, `Rti` and `Ret` end a block,
+/// while `Jpz`, `Jpc` and `Call` continue at both the target and the next instruction. Bytes that
+/// are never reached, or hold an instruction the CPU doesn't have, are left as data, and bytes
+/// read by `Lda`, `Add` or `Sub` or written by `Sta` are variables.
+/// ```
+/// use busyboard::eater::{disassemble_from, Disassembly, I, Isa};
+/// // Lda 5; Hlt; Add 6; Out; 0x2a
+/// let bytes = [0x02, 0x05, 0x0f, 0x04, 0x06, 0x0e, 0x2a];
+///
+/// let disassembly = disassemble_from(&bytes, &[], Isa::Basic, false);
+/// assert!(matches!(disassembly[2], Disassembly::Data { offset: 3, len: 2, .. }));
+/// assert!(matches!(disassembly[3], Disassembly::Variable { offset: 5, value: 0x0e }));
+/// assert!(matches!(disassembly[4], Disassembly::Data { offset: 6, len: 1, .. }));
+///
+/// let disassembly = disassemble_from(&bytes, &[3], Isa::Basic, false);
+/// assert!(matches!(disassembly[2], Disassembly::Instruction { instruction: I::Add(..), offset: 3, .. }));
+/// assert!(matches!(disassembly[3], Disassembly::Instruction { instruction: I::Out(..), offset: 5, .. }));
+/// assert!(matches!(disassembly[4], Disassembly::Variable { offset: 6, value: 0x2a }));
+///
+/// // Call 3; Hlt; Out; Ret
+/// let bytes = [0x10, 0x03, 0x0f, 0x0e, 0x11];
+/// let disassembly = disassemble_from(&bytes, &[], Isa::Extended, false);
+/// assert!(matches!(disassembly[2], Disassembly::Instruction { instruction: I::Out(..), offset: 3, .. }));
+/// assert!(matches!(disassembly[3], Disassembly::Instruction { instruction: I::Ret(..), offset: 4, .. }));
+///
+/// let disassembly = disassemble_from(&bytes, &[], Isa::Basic, false);
+/// assert!(matches!(disassembly[0], Disassembly::Data { offset: 0, len: 5, .. }));
+///
+/// // Ei; Hlt
+/// let disassembly = disassemble_from(&[0x0b, 0x0f], &[], Isa::Basic, true);
+/// assert!(matches!(disassembly[0], Disassembly::Instruction { instruction: I::Ei(..), offset: 0, .. }));
+///
+/// let disassembly = disassemble_from(&[0x0b, 0x0f], &[], Isa::Basic, false);
+/// assert!(matches!(disassembly[0], Disassembly::Data { offset: 0, len: 2, .. }));
+/// ```
+pub fn disassemble_from(bytes: &[u8], entries: &[u8], isa: Isa, interrupts: bool) -> Vec<Disassembly> {
+    classify(bytes, entries, None, isa, interrupts)
+}
+
+/// Disassemble the given bytes, preferring the accesses recorded while executing the program.
+///
+/// Addresses that were fetched as opcodes are decoded before any statically discovered code, and
+/// addresses that were read or written as data become variables. This disassembles code that is
+/// only reachable through self-modification.
+/// ```
+/// use busyboard::eater::{disassemble_from, disassemble_traced, Cpu, Disassembly, Flag, I, Isa};
+/// // Retarget the first jump, so the code at address 3 is no longer statically reachable.
+/// let mut cpu = Cpu::from_asm(vec![
+///     I::jmp(0x03),
+///     I::hlt(),
+///     I::ldi(0x02),
+///     I::sta(0x01),
+///     I::jmp(0x00),
+/// ], vec![]).with_access_map();
+///
+/// while !cpu.get(Flag::Halt) {
+///     cpu.step();
+/// }
+///
+/// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+/// let disassembly = disassemble_from(&bytes, &[], Isa::Basic, false);
+/// assert!(matches!(disassembly[2], Disassembly::Data { offset: 3, .. }));
+///
+/// let disassembly = disassemble_traced(&bytes, &[], cpu.access_map().unwrap(), Isa::Basic, false);
+/// assert!(matches!(disassembly[2], Disassembly::Instruction { instruction: I::Ldi(..), offset: 3, .. }));
+/// assert!(matches!(disassembly[4], Disassembly::Instruction { instruction: I::Jmp(..), offset: 7, .. }));
+/// ```
+pub fn disassemble_traced(bytes: &[u8], entries: &[u8], accesses: &AccessMap, isa: Isa, interrupts: bool) -> Vec<Disassembly> {
+    classify(bytes, entries, Some(accesses), isa, interrupts)
+}
+
+fn classify(bytes: &[u8], entries: &[u8], accesses: Option<&AccessMap>, isa: Isa, interrupts: bool) -> Vec<Disassembly> {
+    let mut starts = vec![false; bytes.len()];
+    let mut covered = vec![false; bytes.len()];
+    let mut variables = vec![false; bytes.len()];
 
-    let mut stack = vec![0_u8];
-    'block: while let Some(offset) = stack.pop() {
-        let mut index = disassembly.iter().position(|d| match d {
-            Disassembly::Data { offset: o, len, .. } => offset >= *o && offset < o + len,
-            Disassembly::Instruction { offset: o, len, .. } => offset >= *o && offset < o + len,
-        }).unwrap();
+    let mut stack = entries.iter().rev().copied().collect::<Vec<_>>();
+    stack.push(0);
 
+    if let Some(accesses) = accesses {
+        for adr in (0..bytes.len()).rev().map(|adr| adr as u8) {
+            if accesses.get(adr, Access::Opcode) {
+                stack.push(adr);
+            }
+
+            if accesses.get(adr, Access::Read) || accesses.get(adr, Access::Write) {
+                variables[adr as usize] = true;
+            }
+        }
+    }
+
+    'block: while let Some(mut offset) = stack.pop() {
         loop {
-            if let Disassembly::Data { ref mut data, ref mut len, ref mut offset } = disassembly[index] {
-                let instruction = match I::from_opcode(data[0]) {
-                    IBuilder::Complete(instruction) => instruction,
-                    IBuilder::NeedsData(incomplete) if  *len > 1 => incomplete.with_data(data[1]),
-                    IBuilder::NeedsData(_) => continue 'block,
-                    IBuilder::Invalid => continue 'block,
-                };
-
-                let instruction_len = match instruction {
-                    I::Nop(..) | I::Hlt(..) | I::Out(..) => 1,
-                    I::Ldi(..) | I::Lda(..) | I::Sta(..) | I::Add(..) | I::Sub(..) => 2,
-                    I::Jmp(..) | I::Jpz(..) | I::Jpc(..) => {
-                        if (data[1] as usize) < bytes.len() {
-                            stack.push(data[1]);
-                        }
-                        2
-                    },
-                };
-
-                let instruction = Disassembly::Instruction {
-                    instruction,
-                    len: instruction_len,
-                    offset: *offset,
-                };
-
-                *offset = *offset + instruction_len;
-                *data = data.split_off(instruction_len as usize);
-                *len = *len - instruction_len;
-
-                disassembly.insert(index, instruction);
-                index += 1;
-            } else {
+            let index = offset as usize;
+            if index >= bytes.len() || covered[index] {
                 continue 'block;
             }
+
+            let Some(instruction) = decode(bytes, index, isa, interrupts) else {
+                continue 'block;
+            };
+
+            let len = size(&instruction);
+            if covered[index..index + len as usize].iter().any(|c| *c) {
+                continue 'block;
+            }
+
+            starts[index] = true;
+            covered[index..index + len as usize].fill(true);
+
+            match instruction {
+                I::Lda(..) | I::Sta(..) | I::Add(..) | I::Sub(..) => {
+                    let address = bytes[index + 1] as usize;
+                    if address < bytes.len() {
+                        variables[address] = true;
+                    }
+                },
+                // A subroutine returns to the instruction after the call
+                I::Jpz(..) | I::Jpc(..) | I::Call(..) => stack.push(bytes[index + 1]),
+                I::Jmp(..) => {
+                    stack.push(bytes[index + 1]);
+                    continue 'block;
+                },
+                I::Hlt(..) | I::Rti(..) | I::Ret(..) => continue 'block,
+                _ => {},
+            }
+
+            offset = match offset.checked_add(len) {
+                Some(next) => next,
+                None => continue 'block,
+            };
+        }
+    }
+
+    let mut disassembly = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let offset = index as u8;
+
+        if starts[index] {
+            let instruction = decode(bytes, index, isa, interrupts).unwrap();
+            let len = size(&instruction);
+
+            disassembly.push(Disassembly::Instruction { instruction, len, offset });
+            index += len as usize;
+        } else if variables[index] && !covered[index] {
+            disassembly.push(Disassembly::Variable { value: bytes[index], offset });
+            index += 1;
+        } else {
+            let end = (index..bytes.len())
+                .find(|&i| starts[i] || (variables[i] && !covered[i]))
+                .unwrap_or(bytes.len())
+                .clamp(index + 1, index + u8::MAX as usize);
+
+            let data = bytes[index..end].to_vec();
+            disassembly.push(Disassembly::Data { len: data.len() as u8, data, offset });
+            index = end;
         }
     }
 
     disassembly
 }
+
+fn decode(bytes: &[u8], index: usize, isa: Isa, interrupts: bool) -> Option<I> {
+    let instruction = match I::from_opcode(bytes[index]) {
+        IBuilder::Complete(instruction) => Some(instruction),
+        IBuilder::NeedsData(incomplete) => bytes.get(index + 1).map(|data| incomplete.with_data(*data)),
+        IBuilder::Invalid => None,
+    };
+
+    instruction.filter(|instruction| supports(isa, interrupts, instruction))
+}
+
+fn size(instruction: &I) -> u8 {
+    match instruction {
+        I::Nop(..) | I::Hlt(..) | I::In(..) | I::Out(..) | I::Rti(..) | I::Ei(..) | I::Di(..) |
+            I::Ret(..) | I::Push(..) | I::Pop(..) => 1,
+        I::Ldi(..) | I::Lda(..) | I::Sta(..) | I::Add(..) | I::Sub(..) |
+            I::Jmp(..) | I::Jpz(..) | I::Jpc(..) | I::Call(..) => 2,
+    }
+}
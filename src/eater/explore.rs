@@ -0,0 +1,208 @@
+use super::{Cpu, Flag, State};
+use std::collections::{BTreeMap, HashMap};
+
+/// The values written to RAM before a run, as `(address, value)` pairs.
+pub type Input = Vec<(u8, u8)>;
+
+/// Explores every state reachable from a program image for every combination of input bytes.
+pub struct Explorer {
+    image: State,
+    inputs: Vec<(u8, Vec<u8>)>,
+    limit: usize,
+}
+
+/// What an `Explorer` learned about a program. Each possible behavior is paired with an input
+/// that produces it. It is `complete` unless the state limit was reached or the program executed
+/// `In`, which the explorer reads as 0, so other behaviors may be possible.
+#[derive(Debug, Default)]
+pub struct Exploration {
+    pub halt: Option<Input>,
+    pub fault: Option<Input>,
+    pub loops: Option<Input>,
+    pub outputs: BTreeMap<u8, Input>,
+    pub states: usize,
+    pub complete: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Outcome {
+    Halt,
+    Fault,
+    Loop,
+}
+
+impl Explorer {
+    /// Explore the program as it is currently loaded in the given CPU, under its memory map, ISA
+    /// and interrupts. Devices are ignored: their addresses read and write as memory, and they
+    /// never request an interrupt.
+    /// ```
+    /// use busyboard::eater::{Cpu, Explorer, I, MemoryMap, Policy};
+    /// // Write the input over the program's first byte unless it is zero.
+    /// let memory = MemoryMap::new(256).with_rom(0x00..=0x07).with_rom_write(Policy::Fault);
+    /// let cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x08),
+    ///     I::jpz(0x06),
+    ///     I::sta(0x00),
+    ///     I::hlt(),
+    /// ], vec![0x00]).with_memory_map(memory);
+    ///
+    /// let exploration = Explorer::new(&cpu).input(0x08, 0..2).explore();
+    /// assert_eq!(exploration.halt, Some(vec![(0x08, 0)]));
+    /// assert_eq!(exploration.fault, Some(vec![(0x08, 1)]));
+    /// ```
+    pub fn new(cpu: &Cpu) -> Self {
+        Self {
+            image: State::of(cpu),
+            inputs: vec![],
+            limit: 1 << 20,
+        }
+    }
+
+    /// Try each of the given values at the given address.
+    pub fn input<V>(mut self, adr: u8, values: V) -> Self
+    where
+        V: IntoIterator<Item = u8>,
+    {
+        self.inputs.push((adr, values.into_iter().collect()));
+        self
+    }
+
+    /// Stop exploring after visiting the given number of distinct states.
+    pub fn with_limit(mut self, states: usize) -> Self {
+        self.limit = states;
+        self
+    }
+
+    /// Run the program for every combination of inputs. States shared between runs are only
+    /// explored once.
+    /// ```
+    /// use busyboard::eater::{Cpu, Explorer, I};
+    /// // Output the input, then halt unless it is zero.
+    /// let cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x07),
+    ///     I::out(),
+    ///     I::jpz(0x00),
+    ///     I::hlt(),
+    /// ], vec![0x00]);
+    ///
+    /// let exploration = Explorer::new(&cpu).input(0x07, 0..4).explore();
+    /// assert!(exploration.complete);
+    /// assert_eq!(exploration.halt, Some(vec![(0x07, 1)]));
+    /// assert_eq!(exploration.loops, Some(vec![(0x07, 0)]));
+    /// assert_eq!(exploration.fault, None);
+    /// assert_eq!(exploration.outputs.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    ///
+    /// // Output what `In` reads, which isn't explored.
+    /// let cpu = Cpu::from_asm(vec![I::input(), I::out(), I::hlt()], vec![]);
+    /// let exploration = Explorer::new(&cpu).explore();
+    /// assert!(!exploration.complete);
+    /// assert!(exploration.can_halt());
+    /// ```
+    pub fn explore(&self) -> Exploration {
+        let mut exploration = Exploration { complete: true, ..Default::default() };
+        let mut outcomes: HashMap<State, Option<Outcome>> = HashMap::new();
+
+        let mut limited = false;
+        let mut indices = vec![0; self.inputs.len()];
+        if self.inputs.iter().any(|(_, values)| values.is_empty()) {
+            return exploration;
+        }
+
+        loop {
+            let input: Input = self.inputs.iter().zip(&indices)
+                .map(|((adr, values), i)| (*adr, values[*i]))
+                .collect();
+
+            let mut state = self.image.clone();
+            for (adr, value) in &input {
+                state.store(*adr, *value);
+            }
+
+            let mut path = vec![];
+            let outcome = loop {
+                if state.flags & (1 << Flag::Halt as u8) != 0 {
+                    break Outcome::Halt;
+                } else if state.flags & (1 << Flag::IllegalHalt as u8) != 0 {
+                    break Outcome::Fault;
+                }
+
+                match outcomes.get(&state) {
+                    Some(Some(outcome)) => break *outcome,
+                    Some(None) => break Outcome::Loop,
+                    None => {},
+                }
+
+                if outcomes.len() >= self.limit {
+                    limited = true;
+                    break Outcome::Loop;
+                }
+
+                let (next, out, read) = state.step();
+                if read {
+                    exploration.complete = false;
+                }
+                if let Some(value) = out {
+                    exploration.outputs.entry(value).or_insert_with(|| input.clone());
+                }
+
+                outcomes.insert(state.clone(), None);
+                path.push(state);
+                state = next;
+            };
+
+            if limited {
+                exploration.complete = false;
+                break;
+            }
+
+            for state in path {
+                outcomes.insert(state, Some(outcome));
+            }
+
+            let counterexample = match outcome {
+                Outcome::Halt => &mut exploration.halt,
+                Outcome::Fault => &mut exploration.fault,
+                Outcome::Loop => &mut exploration.loops,
+            };
+            counterexample.get_or_insert(input);
+
+            if !next(&mut indices, &self.inputs) {
+                break;
+            }
+        }
+
+        exploration.states = outcomes.len();
+        exploration
+    }
+}
+
+/// Advance to the next combination of inputs, returning false once all have been tried.
+fn next(indices: &mut [usize], inputs: &[(u8, Vec<u8>)]) -> bool {
+    for (i, (_, values)) in indices.iter_mut().zip(inputs) {
+        *i += 1;
+        if *i < values.len() {
+            return true;
+        }
+
+        *i = 0;
+    }
+
+    false
+}
+
+impl Exploration {
+    /// Returns true if some input makes the program halt.
+    pub fn can_halt(&self) -> bool {
+        self.halt.is_some()
+    }
+
+    /// Returns true if some input makes the program execute an illegal instruction or access.
+    pub fn can_fault(&self) -> bool {
+        self.fault.is_some()
+    }
+
+    /// Returns true if some input makes the program run forever.
+    pub fn can_loop(&self) -> bool {
+        self.loops.is_some()
+    }
+}
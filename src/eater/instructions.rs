@@ -1,4 +1,4 @@
-use super::{Cpu, Flag};
+use super::{Access, Cpu, Flag};
 
 pub enum I {
     Nop(Nop),
@@ -10,8 +10,16 @@ pub enum I {
     Jmp(Jmp),
     Jpz(Jpz),
     Jpc(Jpc),
+    In(In),
     Out(Out),
     Hlt(Hlt),
+    Rti(Rti),
+    Ei(Ei),
+    Di(Di),
+    Call(Call),
+    Ret(Ret),
+    Push(Push),
+    Pop(Pop),
 }
 
 impl I {
@@ -185,6 +193,27 @@ impl I {
         I::Jpc(Jpc(address))
     }
 
+    /// Load the value returned by the `in` function into register A.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut values = vec![0x2a, 0x07].into_iter();
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::input(),
+    ///     I::input(),
+    ///     I::hlt()
+    /// ], vec![]).with_in(move || values.next().unwrap_or(0));
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 0x2a);
+    /// assert_eq!(cpu.ip(), 1);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 0x07);
+    /// ```
+    pub fn input() -> Self {
+        I::In(In)
+    }
+
     /// Call the `out` function with the contents of register A.
     /// ```
     /// use busyboard::eater::{Cpu, I};
@@ -228,6 +257,98 @@ impl I {
         I::Hlt(Hlt)
     }
 
+    /// Return from an interrupt handler to the saved address, and enable interrupts again. Only
+    /// decoded when the CPU has interrupts.
+    pub fn rti() -> Self {
+        I::Rti(Rti)
+    }
+
+    /// Enable interrupts. Only decoded when the CPU has interrupts.
+    /// ```
+    /// use busyboard::eater::{Cpu, Flag, I, Interrupts};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ei(),
+    ///     I::di(),
+    ///     I::hlt(),
+    /// ], vec![]).with_interrupts(Interrupts::new(0x80));
+    ///
+    /// cpu.step();
+    /// assert!(cpu.get(Flag::InterruptEnable));
+    /// cpu.step();
+    /// assert!(!cpu.get(Flag::InterruptEnable));
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![I::ei()], vec![]);
+    /// cpu.step();
+    /// assert!(cpu.get(Flag::IllegalHalt));
+    /// ```
+    pub fn ei() -> Self {
+        I::Ei(Ei)
+    }
+
+    /// Disable interrupts. Only decoded when the CPU has interrupts.
+    pub fn di() -> Self {
+        I::Di(Di)
+    }
+
+    /// Push the address of the next instruction onto the stack and jump to the given address. Only
+    /// decoded with the extended instruction set.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Isa};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::call(0x03),
+    ///     I::hlt(),
+    ///     I::ldi(0x2a),
+    ///     I::ret(),
+    /// ], vec![]).with_isa(Isa::Extended);
+    ///
+    /// cpu.step();
+    /// assert_eq!(cpu.ip(), 0x03);
+    /// assert_eq!(cpu.sp(), 0xff);
+    /// assert_eq!(cpu.read(0xff), Some(0x02));
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(cpu.ip(), 0x02);
+    /// assert_eq!(cpu.sp(), 0x00);
+    /// assert_eq!(cpu.a(), 0x2a);
+    ///
+    /// let mut cpu = Cpu::from_asm(vec![I::call(0x03)], vec![]);
+    /// cpu.step();
+    /// assert!(cpu.get(busyboard::eater::Flag::IllegalHalt));
+    /// ```
+    pub fn call(address: u8) -> Self {
+        I::Call(Call(address))
+    }
+
+    /// Pop an address off the stack and jump to it. Only decoded with the extended instruction set.
+    pub fn ret() -> Self {
+        I::Ret(Ret)
+    }
+
+    /// Push register A onto the stack. Only decoded with the extended instruction set.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Isa};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x07),
+    ///     I::push(),
+    ///     I::ldi(0x00),
+    ///     I::pop(),
+    ///     I::hlt(),
+    /// ], vec![]).with_isa(Isa::Extended);
+    ///
+    /// cpu.run();
+    /// assert_eq!(cpu.a(), 0x07);
+    /// assert_eq!(cpu.sp(), 0x00);
+    /// ```
+    pub fn push() -> Self {
+        I::Push(Push)
+    }
+
+    /// Pop the top of the stack into register A. Only decoded with the extended instruction set.
+    pub fn pop() -> Self {
+        I::Pop(Pop)
+    }
+
     pub (super) fn from_opcode(opcode: u8) -> IBuilder {
         if opcode == Nop::opcode() {
             IBuilder::Complete(I::Nop(Nop))
@@ -247,14 +368,47 @@ impl I {
             IBuilder::NeedsData(IWithoutData(I::Jpz(Jpz(0))))
         } else if opcode == Jpc::opcode() {
             IBuilder::NeedsData(IWithoutData(I::Jpc(Jpc(0))))
+        } else if opcode == In::opcode() {
+            IBuilder::Complete(I::In(In))
         } else if opcode == Out::opcode() {
             IBuilder::Complete(I::Out(Out))
         } else if opcode == Hlt::opcode() {
             IBuilder::Complete(I::Hlt(Hlt))
+        } else if opcode == Rti::opcode() {
+            IBuilder::Complete(I::Rti(Rti))
+        } else if opcode == Ei::opcode() {
+            IBuilder::Complete(I::Ei(Ei))
+        } else if opcode == Di::opcode() {
+            IBuilder::Complete(I::Di(Di))
+        } else if opcode == Call::opcode() {
+            IBuilder::NeedsData(IWithoutData(I::Call(Call(0))))
+        } else if opcode == Ret::opcode() {
+            IBuilder::Complete(I::Ret(Ret))
+        } else if opcode == Push::opcode() {
+            IBuilder::Complete(I::Push(Push))
+        } else if opcode == Pop::opcode() {
+            IBuilder::Complete(I::Pop(Pop))
         } else {
             IBuilder::Invalid
         }
     }
+
+    /// Returns where a jump goes when it is taken.
+    pub (super) fn target(&self) -> Option<u8> {
+        match self {
+            I::Jmp(Jmp(target)) | I::Jpz(Jpz(target)) | I::Jpc(Jpc(target)) | I::Call(Call(target)) => Some(*target),
+            _ => None,
+        }
+    }
+
+    /// Returns whether a conditional jump jumps, given the CPU's state when it executes.
+    pub (super) fn taken(&self, cpu: &Cpu) -> Option<bool> {
+        match self {
+            I::Jpz(..) => Some(cpu.a == 0),
+            I::Jpc(..) => Some(cpu.get(Flag::Carry)),
+            _ => None,
+        }
+    }
 }
 
 pub (super) enum IBuilder {
@@ -276,6 +430,7 @@ impl IWithoutData {
             I::Jmp(_) => I::Jmp(Jmp(data)),
             I::Jpz(_) => I::Jpz(Jpz(data)),
             I::Jpc(_) => I::Jpc(Jpc(data)),
+            I::Call(_) => I::Call(Call(data)),
             instr => instr,
         }
     }
@@ -285,6 +440,9 @@ pub (super) trait Instruction {
     fn assemble(&self) -> Vec<u8>;
     fn execute(&self, cpu: &mut Cpu);
     fn next(&self, cpu: &Cpu) -> u8;
+    /// The T-states it takes, once executed: 2 to fetch the opcode, 2 more to fetch an operand,
+    /// then a step per microcode operation.
+    fn cycles(&self, cpu: &Cpu) -> u8;
 }
 
 pub struct Nop;
@@ -350,6 +508,13 @@ impl Jpc {
     }
 }
 
+pub struct In;
+impl In {
+    fn opcode() -> u8 {
+        9
+    }
+}
+
 pub struct Out;
 impl Out {
     fn opcode() -> u8 {
@@ -364,6 +529,55 @@ impl Hlt {
     }
 }
 
+pub struct Rti;
+impl Rti {
+    fn opcode() -> u8 {
+        10
+    }
+}
+
+pub struct Ei;
+impl Ei {
+    fn opcode() -> u8 {
+        11
+    }
+}
+
+pub struct Di;
+impl Di {
+    fn opcode() -> u8 {
+        12
+    }
+}
+
+pub struct Call(u8);
+impl Call {
+    fn opcode() -> u8 {
+        16
+    }
+}
+
+pub struct Ret;
+impl Ret {
+    fn opcode() -> u8 {
+        17
+    }
+}
+
+pub struct Push;
+impl Push {
+    fn opcode() -> u8 {
+        18
+    }
+}
+
+pub struct Pop;
+impl Pop {
+    fn opcode() -> u8 {
+        19
+    }
+}
+
 impl Instruction for I {
     fn assemble(&self) -> Vec<u8> {
         match self {
@@ -376,8 +590,16 @@ impl Instruction for I {
             I::Jmp(jmp) => jmp.assemble(),
             I::Jpz(jpz) => jpz.assemble(),
             I::Jpc(jpc) => jpc.assemble(),
+            I::In(inp) => inp.assemble(),
             I::Out(out) => out.assemble(),
             I::Hlt(hlt) => hlt.assemble(),
+            I::Rti(rti) => rti.assemble(),
+            I::Ei(ei) => ei.assemble(),
+            I::Di(di) => di.assemble(),
+            I::Call(call) => call.assemble(),
+            I::Ret(ret) => ret.assemble(),
+            I::Push(push) => push.assemble(),
+            I::Pop(pop) => pop.assemble(),
         }
     }
 
@@ -392,8 +614,16 @@ impl Instruction for I {
             I::Jmp(jmp) => jmp.execute(cpu),
             I::Jpz(jpz) => jpz.execute(cpu),
             I::Jpc(jpc) => jpc.execute(cpu),
+            I::In(inp) => inp.execute(cpu),
             I::Out(out) => out.execute(cpu),
             I::Hlt(hlt) => hlt.execute(cpu),
+            I::Rti(rti) => rti.execute(cpu),
+            I::Ei(ei) => ei.execute(cpu),
+            I::Di(di) => di.execute(cpu),
+            I::Call(call) => call.execute(cpu),
+            I::Ret(ret) => ret.execute(cpu),
+            I::Push(push) => push.execute(cpu),
+            I::Pop(pop) => pop.execute(cpu),
         }
     }
 
@@ -408,8 +638,40 @@ impl Instruction for I {
             I::Jmp(jmp) => jmp.next(cpu),
             I::Jpz(jpz) => jpz.next(cpu),
             I::Jpc(jpc) => jpc.next(cpu),
+            I::In(inp) => inp.next(cpu),
             I::Out(out) => out.next(cpu),
             I::Hlt(hlt) => hlt.next(cpu),
+            I::Rti(rti) => rti.next(cpu),
+            I::Ei(ei) => ei.next(cpu),
+            I::Di(di) => di.next(cpu),
+            I::Call(call) => call.next(cpu),
+            I::Ret(ret) => ret.next(cpu),
+            I::Push(push) => push.next(cpu),
+            I::Pop(pop) => pop.next(cpu),
+        }
+    }
+
+    fn cycles(&self, cpu: &Cpu) -> u8 {
+        match self {
+            I::Nop(nop) => nop.cycles(cpu),
+            I::Ldi(ldi) => ldi.cycles(cpu),
+            I::Lda(lda) => lda.cycles(cpu),
+            I::Sta(sta) => sta.cycles(cpu),
+            I::Add(add) => add.cycles(cpu),
+            I::Sub(sub) => sub.cycles(cpu),
+            I::Jmp(jmp) => jmp.cycles(cpu),
+            I::Jpz(jpz) => jpz.cycles(cpu),
+            I::Jpc(jpc) => jpc.cycles(cpu),
+            I::In(inp) => inp.cycles(cpu),
+            I::Out(out) => out.cycles(cpu),
+            I::Hlt(hlt) => hlt.cycles(cpu),
+            I::Rti(rti) => rti.cycles(cpu),
+            I::Ei(ei) => ei.cycles(cpu),
+            I::Di(di) => di.cycles(cpu),
+            I::Call(call) => call.cycles(cpu),
+            I::Ret(ret) => ret.cycles(cpu),
+            I::Push(push) => push.cycles(cpu),
+            I::Pop(pop) => pop.cycles(cpu),
         }
     }
 }
@@ -422,7 +684,11 @@ impl Instruction for Nop {
     fn execute(&self, _cpu: &mut Cpu) {}
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 1
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        2 // Only the fetch: CO MI, RO II CE
     }
 }
 
@@ -433,10 +699,15 @@ impl Instruction for Ldi {
 
     fn execute(&self, cpu: &mut Cpu) {
         cpu.a = self.0;
+        cpu.bus = self.0;
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 2
+        cpu.ip.wrapping_add(2)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        4 // CO MI, RO AI CE
     }
 }
 
@@ -446,7 +717,10 @@ impl Instruction for Lda {
     }
 
     fn execute(&self, cpu: &mut Cpu) {
-        if let Some(a) = cpu.read(self.0) {
+        if let Some(a) = cpu.load_byte(self.0) {
+            cpu.record(self.0, Access::Read);
+            cpu.mar = self.0;
+            cpu.bus = a;
             cpu.a = a;
         } else {
             cpu.set(Flag::IllegalHalt);
@@ -454,7 +728,11 @@ impl Instruction for Lda {
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 2
+        cpu.ip.wrapping_add(2)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        5 // CO MI, RO MI CE, RO AI
     }
 }
 
@@ -464,11 +742,17 @@ impl Instruction for Sta {
     }
 
     fn execute(&self, cpu: &mut Cpu) {
-        cpu.write(self.0, cpu.a);
+        cpu.store_byte(self.0, cpu.a);
+        cpu.mar = self.0;
+        cpu.bus = cpu.a;
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 2
+        cpu.ip.wrapping_add(2)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        5 // CO MI, RO MI CE, AO RI
     }
 }
 
@@ -480,19 +764,27 @@ impl Instruction for Add {
     fn execute(&self, cpu: &mut Cpu) {
         cpu.unset(Flag::Carry);
 
-        if let Some(operand) = cpu.read(self.0) {
+        if let Some(operand) = cpu.load_byte(self.0) {
+            cpu.record(self.0, Access::Read);
+            cpu.mar = self.0;
+            cpu.b = operand;
             if cpu.a > 0xFF - operand {
                 cpu.set(Flag::Carry);
             }
 
             cpu.a = cpu.a.wrapping_add(operand);
+            cpu.bus = cpu.a;
         } else {
             cpu.set(Flag::IllegalHalt);
         }
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 2
+        cpu.ip.wrapping_add(2)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        6 // CO MI, RO MI CE, RO BI, EO AI FI
     }
 }
 
@@ -504,19 +796,27 @@ impl Instruction for Sub {
     fn execute(&self, cpu: &mut Cpu) {
         cpu.unset(Flag::Carry);
 
-        if let Some(operand) = cpu.read(self.0) {
+        if let Some(operand) = cpu.load_byte(self.0) {
+            cpu.record(self.0, Access::Read);
+            cpu.mar = self.0;
+            cpu.b = operand;
             if cpu.a < operand {
                 cpu.set(Flag::Carry);
             }
 
             cpu.a = cpu.a.wrapping_sub(operand);
+            cpu.bus = cpu.a;
         } else {
             cpu.set(Flag::IllegalHalt);
         }
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 2
+        cpu.ip.wrapping_add(2)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        6 // CO MI, RO MI CE, RO BI, EO AI SU FI
     }
 }
 
@@ -530,6 +830,10 @@ impl Instruction for Jmp {
     fn next(&self, _cpu: &Cpu) -> u8 {
         self.0
     }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        4 // CO MI, RO J
+    }
 }
 
 impl Instruction for Jpz { 
@@ -543,9 +847,14 @@ impl Instruction for Jpz {
         if cpu.a == 0 {
             self.0
         } else {
-            cpu.ip + 2
+            cpu.ip.wrapping_add(2)
         }
     }
+
+    fn cycles(&self, cpu: &Cpu) -> u8 {
+        // Without the jump, the operand is skipped rather than fetched
+        if cpu.a == 0 { 4 } else { 3 }
+    }
 }
 
 impl Instruction for Jpc {
@@ -559,9 +868,35 @@ impl Instruction for Jpc {
         if cpu.get(Flag::Carry) {
             self.0
         } else {
-            cpu.ip + 2
+            cpu.ip.wrapping_add(2)
         }
     }
+
+    fn cycles(&self, cpu: &Cpu) -> u8 {
+        if cpu.get(Flag::Carry) { 4 } else { 3 }
+    }
+}
+
+impl Instruction for In {
+    fn assemble(&self) -> Vec<u8> {
+        vec![In::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        cpu.a = (cpu.input)();
+        cpu.bus = cpu.a;
+
+        // What comes next depends on the input, so a repeated state is no longer a loop
+        cpu.reset_loop_detection();
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // IN AI
+    }
 }
 
 impl Instruction for Out {
@@ -570,11 +905,16 @@ impl Instruction for Out {
     }
 
     fn execute(&self, cpu: &mut Cpu) {
+        cpu.bus = cpu.a;
         (cpu.out)(cpu.a);
     }
 
     fn next(&self, cpu: &Cpu) -> u8 {
-        cpu.ip + 1
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // AO OI
     }
 }
 
@@ -590,4 +930,188 @@ impl Instruction for Hlt {
     fn next(&self, cpu: &Cpu) -> u8 {
         cpu.ip
     }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // HLT
+    }
+}
+
+impl Instruction for Rti {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Rti::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        if let Some(interrupts) = cpu.interrupts {
+            cpu.bus = interrupts.saved;
+        }
+        cpu.set(Flag::InterruptEnable);
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.interrupts.map_or(cpu.ip, |interrupts| interrupts.saved)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // RA J EI
+    }
+}
+
+impl Instruction for Ei {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Ei::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        cpu.set(Flag::InterruptEnable);
+
+        // What comes next depends on when a request comes, so a repeated state is no longer a loop
+        cpu.reset_loop_detection();
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // EI
+    }
+}
+
+impl Instruction for Di {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Di::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        cpu.unset(Flag::InterruptEnable);
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        3 // DI
+    }
+}
+
+impl Instruction for Call {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Call::opcode(), self.0]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        cpu.sp = cpu.sp.wrapping_sub(1);
+        cpu.store_byte(cpu.sp, cpu.ip.wrapping_add(2));
+        cpu.mar = cpu.sp;
+        cpu.bus = cpu.ip.wrapping_add(2);
+    }
+
+    fn next(&self, _cpu: &Cpu) -> u8 {
+        self.0
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        8 // CO MI, RO BI CE, SD, SO MI, CO RI, BO J
+    }
+}
+
+impl Instruction for Ret {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Ret::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        // The return address stays on the bus for `next` to jump to
+        if let Some(address) = cpu.load_byte(cpu.sp) {
+            cpu.record(cpu.sp, Access::Read);
+            cpu.mar = cpu.sp;
+            cpu.bus = address;
+            cpu.sp = cpu.sp.wrapping_add(1);
+        } else {
+            cpu.set(Flag::IllegalHalt);
+        }
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.bus
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        5 // SO MI, RO J, SI
+    }
+}
+
+impl Instruction for Push {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Push::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        cpu.sp = cpu.sp.wrapping_sub(1);
+        cpu.store_byte(cpu.sp, cpu.a);
+        cpu.mar = cpu.sp;
+        cpu.bus = cpu.a;
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        5 // SD, SO MI, AO RI
+    }
+}
+
+impl Instruction for Pop {
+    fn assemble(&self) -> Vec<u8> {
+        vec![Pop::opcode()]
+    }
+
+    fn execute(&self, cpu: &mut Cpu) {
+        if let Some(a) = cpu.load_byte(cpu.sp) {
+            cpu.record(cpu.sp, Access::Read);
+            cpu.mar = cpu.sp;
+            cpu.bus = a;
+            cpu.a = a;
+            cpu.sp = cpu.sp.wrapping_add(1);
+        } else {
+            cpu.set(Flag::IllegalHalt);
+        }
+    }
+
+    fn next(&self, cpu: &Cpu) -> u8 {
+        cpu.ip.wrapping_add(1)
+    }
+
+    fn cycles(&self, _cpu: &Cpu) -> u8 {
+        5 // SO MI, RO AI, SI
+    }
+}
+
+impl std::fmt::Display for I {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            I::Nop(..) => write!(f, "Nop"),
+            I::Ldi(Ldi(data)) => write!(f, "Ldi {:#04x}", data),
+            I::Lda(Lda(data)) => write!(f, "Lda {:#04x}", data),
+            I::Sta(Sta(data)) => write!(f, "Sta {:#04x}", data),
+            I::Add(Add(data)) => write!(f, "Add {:#04x}", data),
+            I::Sub(Sub(data)) => write!(f, "Sub {:#04x}", data),
+            I::Jmp(Jmp(data)) => write!(f, "Jmp {:#04x}", data),
+            I::Jpz(Jpz(data)) => write!(f, "Jpz {:#04x}", data),
+            I::Jpc(Jpc(data)) => write!(f, "Jpc {:#04x}", data),
+            I::In(..) => write!(f, "In"),
+            I::Out(..) => write!(f, "Out"),
+            I::Hlt(..) => write!(f, "Hlt"),
+            I::Rti(..) => write!(f, "Rti"),
+            I::Ei(..) => write!(f, "Ei"),
+            I::Di(..) => write!(f, "Di"),
+            I::Call(Call(data)) => write!(f, "Call {:#04x}", data),
+            I::Ret(..) => write!(f, "Ret"),
+            I::Push(..) => write!(f, "Push"),
+            I::Pop(..) => write!(f, "Pop"),
+        }
+    }
 }
@@ -0,0 +1,84 @@
+use crate::config::Config;
+
+/// The interrupt line. While `Flag::InterruptEnable` is set, a request raised with `Cpu::irq`, or by
+/// a device, makes the CPU save the address of the next instruction and jump to the vector instead,
+/// clearing the flag. `Rti` jumps back and sets it again, and `Ei` and `Di` set and clear it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Interrupts {
+    pub (super) vector: u8,
+    pub (super) requested: bool,
+    pub (super) saved: u8,
+}
+
+impl Interrupts {
+    /// Interrupts that jump to the handler at `vector`.
+    /// ```
+    /// use busyboard::eater::{Cpu, Flag, I, Interrupts};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ei(),
+    ///     I::nop(),
+    ///     I::nop(),
+    ///     I::hlt(),
+    ///     I::ldi(0x2a),
+    ///     I::rti(),
+    /// ], vec![]).with_interrupts(Interrupts::new(0x04));
+    ///
+    /// cpu.step();
+    /// cpu.irq();
+    /// cpu.step();
+    /// assert_eq!(cpu.ip(), 0x04);
+    /// assert!(!cpu.get(Flag::InterruptEnable));
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(cpu.ip(), 0x02);
+    /// assert_eq!(cpu.a(), 0x2a);
+    /// assert!(cpu.get(Flag::InterruptEnable));
+    /// ```
+    pub fn new(vector: u8) -> Self {
+        Self { vector, requested: false, saved: 0 }
+    }
+
+    /// Returns the address of the handler.
+    pub fn vector(&self) -> u8 {
+        self.vector
+    }
+
+    /// Returns true if a request was raised and not yet handled.
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Returns the address `Rti` returns to.
+    pub fn saved(&self) -> u8 {
+        self.saved
+    }
+
+    /// The interrupts in the `[interrupts]` table of the configuration, which holds the `vector`
+    /// address. Without the table there are none, and `Rti`, `Ei` and `Di` halt as illegal.
+    /// ```
+    /// use busyboard::{config::Config, eater::Interrupts};
+    /// let config = Config::parse("[interrupts]\nvector = 0x40").unwrap();
+    /// assert_eq!(Interrupts::from(&config).unwrap().map(|interrupts| interrupts.vector()), Some(0x40));
+    ///
+    /// let config = Config::parse("").unwrap();
+    /// assert_eq!(Interrupts::from(&config).unwrap(), None);
+    /// ```
+    pub fn from(config: &Config) -> Result<Option<Interrupts>, String> {
+        let mut interrupts = None;
+
+        for entry in config.table("interrupts") {
+            let line = entry.line + 1;
+
+            match entry.key.as_str() {
+                "vector" => interrupts = match entry.value.as_integer() {
+                    Some(vector) if (0..=0xff).contains(&vector) => Some(Interrupts::new(vector as u8)),
+                    _ => return Err(format!("line {}: `vector` expects an address", line)),
+                },
+                key => return Err(format!("line {}: unknown setting `{}`", line, key)),
+            }
+        }
+
+        Ok(interrupts)
+    }
+}
@@ -0,0 +1,57 @@
+use super::I;
+use crate::config::Config;
+
+/// The instructions the CPU decodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Isa {
+    /// The breadboard's instructions, and `Rti`, `Ei` and `Di` with interrupts.
+    #[default]
+    Basic,
+    /// The basic instructions plus a stack: an SP register, and `Call`, `Ret`, `Push` and `Pop`.
+    /// The stack grows down from the end of memory.
+    Extended,
+}
+
+impl Isa {
+    /// The instruction set in the `[machine]` table of the configuration, where `isa` is `"basic"` or
+    /// `"extended"`.
+    /// ```
+    /// use busyboard::{config::Config, eater::Isa};
+    /// let config = Config::parse("[machine]\nisa = \"extended\"").unwrap();
+    /// assert_eq!(Isa::from(&config), Ok(Isa::Extended));
+    ///
+    /// let config = Config::parse("").unwrap();
+    /// assert_eq!(Isa::from(&config), Ok(Isa::Basic));
+    /// ```
+    pub fn from(config: &Config) -> Result<Isa, String> {
+        let mut isa = Isa::default();
+
+        for entry in config.table("machine") {
+            let line = entry.line + 1;
+
+            match entry.key.as_str() {
+                "isa" => isa = match entry.value.as_str() {
+                    Some("basic") => Isa::Basic,
+                    Some("extended") => Isa::Extended,
+                    _ => return Err(format!("line {}: `isa` expects \"basic\" or \"extended\"", line)),
+                },
+                key => return Err(format!("line {}: unknown setting `{}`", line, key)),
+            }
+        }
+
+        Ok(isa)
+    }
+
+    /// Returns false for the instructions the set doesn't have, which the CPU halts on as illegal.
+    /// ```
+    /// use busyboard::eater::{I, Isa};
+    /// assert!(!Isa::Basic.supports(&I::call(0x10)));
+    /// assert!(Isa::Extended.supports(&I::call(0x10)));
+    /// ```
+    pub fn supports(self, instruction: &I) -> bool {
+        match instruction {
+            I::Call(..) | I::Ret(..) | I::Push(..) | I::Pop(..) => self == Isa::Extended,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use crate::config::Config;
+use std::ops::RangeInclusive;
+
+/// How the CPU responds when an instruction breaks the memory map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Carry on as the hardware would, dropping a forbidden write.
+    #[default]
+    Ignore,
+    /// Carry on as with `Ignore`, but report it, which `Cpu::warnings` returns.
+    Warn,
+    /// Halt with the reason, which `Cpu::fault` returns.
+    Fault,
+}
+
+/// What fills memory after the program when it is loaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Fill {
+    /// Nothing: memory ends with the program, reading past it halts, and `Sta` grows it up to the
+    /// size.
+    #[default]
+    Grow,
+    /// Zeros, so every address up to the size is valid.
+    Zeros,
+    /// Bytes made from the seed, like RAM when it powers up, so every address up to the size is
+    /// valid but holds garbage until it is written. The same seed gives the same garbage.
+    Random(u64),
+}
+
+/// An access the memory map forbids, at the given address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// A write to a read-only region.
+    Rom(u8),
+    /// A write past the end of memory.
+    PastEnd(u8),
+    /// A read of a byte that was neither loaded with the program nor written since.
+    Uninitialized(u8),
+}
+
+/// The size of memory, what fills it, which regions are read-only, and what happens when `Sta`
+/// writes to either or an instruction reads a byte that was never set.
+/// `Cpu::write` still writes read-only regions, like a ROM programmer, but not past the end.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryMap {
+    size: usize,
+    fill: Fill,
+    rom: Vec<RangeInclusive<u8>>,
+    rom_write: Policy,
+    past_end: Policy,
+    uninitialized: Policy,
+}
+
+impl MemoryMap {
+    /// A memory of `size` bytes, from 1 to 256, with no read-only regions.
+    /// ```
+    /// use busyboard::eater::{Cpu, Flag, I, MemoryMap};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x2a),
+    ///     I::sta(0x10),
+    ///     I::hlt(),
+    /// ], vec![]).with_memory_map(MemoryMap::new(16));
+    ///
+    /// cpu.step();
+    /// cpu.step();
+    /// cpu.step();
+    /// assert!(cpu.get(Flag::Halt));
+    /// assert_eq!(cpu.read(0x10), None);
+    /// ```
+    pub fn new(size: usize) -> Self {
+        Self { size: size.clamp(1, 256), ..Self::default() }
+    }
+
+    /// Returns what fills memory after the program.
+    pub fn fill(&self) -> Fill {
+        self.fill
+    }
+
+    /// Returns true if `Sta` can't write the given address.
+    pub fn is_rom(&self, adr: u8) -> bool {
+        self.rom.iter().any(|range| range.contains(&adr))
+    }
+
+    /// Returns the size of memory in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns what forbids reading the given address, and how the CPU responds, if the byte there
+    /// was never set.
+    pub fn check_read(&self, adr: u8, initialized: bool) -> Option<(Violation, Policy)> {
+        if initialized {
+            None
+        } else {
+            Some((Violation::Uninitialized(adr), self.uninitialized))
+        }
+    }
+
+    /// Returns what forbids writing the given address, and how the CPU responds.
+    pub fn check_write(&self, adr: u8) -> Option<(Violation, Policy)> {
+        if adr as usize >= self.size {
+            Some((Violation::PastEnd(adr), self.past_end))
+        } else if self.is_rom(adr) {
+            Some((Violation::Rom(adr), self.rom_write))
+        } else {
+            None
+        }
+    }
+
+    /// Cut the bytes loaded to the size of memory, then fill it up to the size as `fill` says.
+    pub (super) fn pad(&self, ram: &mut Vec<u8>) {
+        ram.truncate(self.size);
+
+        match self.fill {
+            Fill::Grow => {},
+            Fill::Zeros => ram.resize(self.size, 0),
+            Fill::Random(seed) => {
+                let garbage = garbage(seed).take(self.size).skip(ram.len()).collect::<Vec<_>>();
+                ram.extend(garbage);
+            },
+        }
+    }
+
+    /// Fill memory up to its size as given, instead of ending it with the program.
+    /// ```
+    /// use busyboard::eater::{Cpu, Fill, I, MemoryMap};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x0f),
+    ///     I::hlt(),
+    /// ], vec![]).with_memory_map(MemoryMap::new(16).with_fill(Fill::Zeros));
+    ///
+    /// assert_eq!(cpu.len(), 16);
+    /// cpu.step();
+    /// cpu.step();
+    /// assert_eq!(cpu.a(), 0);
+    ///
+    /// let random = MemoryMap::new(256).with_fill(Fill::Random(7));
+    /// let cpu = Cpu::from_asm(vec![I::hlt()], vec![]).with_memory_map(random);
+    /// assert_eq!(cpu.len(), 256);
+    /// assert_eq!(cpu.read_bytes(0, 1), [0x0f]);
+    /// assert!(cpu.read_bytes(1, 255).iter().any(|&byte| byte != 0));
+    ///
+    /// // A program that runs off its end wraps around, as the breadboard's counter does
+    /// let zeros = MemoryMap::new(256).with_fill(Fill::Zeros);
+    /// let mut cpu = Cpu::from_asm(vec![I::nop()], vec![]).with_memory_map(zeros);
+    /// cpu.write(0xff, 0x01); // Ldi, with its operand at 0x00
+    /// for _ in 0..255 {
+    ///     cpu.step();
+    /// }
+    /// assert_eq!(cpu.ip(), 0xff);
+    /// cpu.step();
+    /// assert_eq!(cpu.ip(), 0x01);
+    /// assert_eq!(cpu.a(), 0x00);
+    /// ```
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Respond to writes past the end of memory with the given policy.
+    pub fn with_past_end(mut self, policy: Policy) -> Self {
+        self.past_end = policy;
+        self
+    }
+
+    /// Make the given addresses read-only.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, MemoryMap, Policy, Stop, Violation};
+    /// let memory = MemoryMap::new(256).with_rom(0x00..=0x03).with_rom_write(Policy::Fault);
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x0f),
+    ///     I::sta(0x02),
+    /// ], vec![]).with_memory_map(memory);
+    ///
+    /// assert_eq!(cpu.run(), Stop::Fault(Violation::Rom(0x02)));
+    /// assert_eq!(cpu.read(0x02), Some(0x03));
+    /// ```
+    pub fn with_rom(mut self, range: RangeInclusive<u8>) -> Self {
+        self.rom.push(range);
+        self
+    }
+
+    /// Respond to writes to read-only regions with the given policy.
+    pub fn with_rom_write(mut self, policy: Policy) -> Self {
+        self.rom_write = policy;
+        self
+    }
+
+    /// Respond to reads of bytes that were never set with the given policy. Bytes are set by loading
+    /// the program, by `Sta` and by `Cpu::write`, but not by the fill.
+    /// ```
+    /// use busyboard::eater::{Cpu, Fill, I, MemoryMap, Policy, Violation};
+    /// let memory = MemoryMap::new(16).with_fill(Fill::Zeros).with_uninitialized(Policy::Warn);
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x01),
+    ///     I::sta(0x0e),
+    ///     I::add(0x0e),
+    ///     I::add(0x0f),
+    ///     I::hlt(),
+    /// ], vec![]).with_memory_map(memory);
+    ///
+    /// cpu.run();
+    /// assert_eq!(cpu.a(), 0x02);
+    /// assert_eq!(cpu.warnings(), [Violation::Uninitialized(0x0f)]);
+    /// ```
+    pub fn with_uninitialized(mut self, policy: Policy) -> Self {
+        self.uninitialized = policy;
+        self
+    }
+
+    /// The memory map in the `[memory]` table of the configuration: a `size`, a `fill` of
+    /// `"grow"`, `"zeros"` or `"random"`, with an optional `seed`, `rom` as a list of `[first, last]`
+    /// address pairs, and `rom-write`, `past-end` and `uninitialized` as `"ignore"`, `"warn"` or
+    /// `"fault"`. Without a seed, random garbage differs from run to run.
+    /// ```
+    /// use busyboard::{config::Config, eater::{Fill, MemoryMap}};
+    /// let config = Config::parse("[memory]\nsize = 32\nrom = [[0, 15]]\nrom-write = \"fault\"").unwrap();
+    /// let memory = MemoryMap::from(&config).unwrap();
+    /// assert_eq!(memory.size(), 32);
+    /// assert!(memory.is_rom(0x0f));
+    /// assert!(!memory.is_rom(0x10));
+    ///
+    /// let config = Config::parse("[memory]\nfill = \"random\"\nseed = 3").unwrap();
+    /// assert_eq!(MemoryMap::from(&config).unwrap().fill(), Fill::Random(3));
+    ///
+    /// let config = Config::parse("[memory]\nrom = [[16, 15]]").unwrap();
+    /// assert!(MemoryMap::from(&config).is_err());
+    /// ```
+    pub fn from(config: &Config) -> Result<MemoryMap, String> {
+        let mut memory = MemoryMap::default();
+        let mut seed = None;
+
+        for entry in config.table("memory") {
+            let line = entry.line + 1;
+
+            match entry.key.as_str() {
+                "size" => memory.size = match entry.value.as_integer() {
+                    Some(size) if (1..=256).contains(&size) => size as usize,
+                    _ => return Err(format!("line {}: `size` expects a number of bytes from 1 to 256", line)),
+                },
+                "fill" => memory.fill = match entry.value.as_str() {
+                    Some("grow") => Fill::Grow,
+                    Some("zeros") => Fill::Zeros,
+                    Some("random") => Fill::Random(0),
+                    _ => return Err(format!("line {}: `fill` expects \"grow\", \"zeros\" or \"random\"", line)),
+                },
+                "seed" => seed = match entry.value.as_integer() {
+                    Some(n) => Some(n as u64),
+                    None => return Err(format!("line {}: `seed` expects a number", line)),
+                },
+                "rom" => for range in entry.value.as_list() {
+                    let bounds = range.as_list().iter().map(|value| value.as_integer()).collect::<Vec<_>>();
+                    match bounds[..] {
+                        [Some(first), Some(last)] if 0 <= first && first <= last && last <= 0xff => {
+                            memory.rom.push(first as u8..=last as u8);
+                        },
+                        _ => return Err(format!("line {}: `rom` expects [first, last] address pairs", line)),
+                    }
+                },
+                key @ ("rom-write" | "past-end" | "uninitialized") => {
+                    let policy = match entry.value.as_str() {
+                        Some("ignore") => Policy::Ignore,
+                        Some("warn") => Policy::Warn,
+                        Some("fault") => Policy::Fault,
+                        _ => return Err(format!("line {}: `{}` expects \"ignore\", \"warn\" or \"fault\"", line, key)),
+                    };
+
+                    match key {
+                        "rom-write" => memory.rom_write = policy,
+                        "past-end" => memory.past_end = policy,
+                        _ => memory.uninitialized = policy,
+                    }
+                },
+                key => return Err(format!("line {}: unknown setting `{}`", line, key)),
+            }
+        }
+
+        if let Fill::Random(_) = memory.fill {
+            let now = || std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+            memory.fill = Fill::Random(seed.unwrap_or_else(|| now().as_nanos() as u64));
+        }
+
+        Ok(memory)
+    }
+}
+
+impl Default for MemoryMap {
+    /// All 256 addresses, none read-only.
+    fn default() -> Self {
+        Self {
+            size: 256,
+            fill: Fill::Grow,
+            rom: vec![],
+            rom_write: Policy::Ignore,
+            past_end: Policy::Ignore,
+            uninitialized: Policy::Ignore,
+        }
+    }
+}
+
+/// Endless bytes from a 64-bit xorshift, which never gets stuck as long as the seed isn't 0.
+fn garbage(seed: u64) -> impl Iterator<Item = u8> {
+    let mut state = seed | 1;
+
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 24) as u8
+    })
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Rom(adr) => write!(f, "write to ROM at {:#04x}", adr),
+            Violation::PastEnd(adr) => write!(f, "write past the end of memory at {:#04x}", adr),
+            Violation::Uninitialized(adr) => write!(f, "uninitialized read at {:#04x}", adr),
+        }
+    }
+}
@@ -1,8 +1,34 @@
+mod access;
+mod assembler;
+mod coverage;
 mod cpu;
+mod detector;
+mod devices;
 mod disassemble;
+mod explore;
 mod instructions;
+mod interrupts;
+mod isa;
+mod memory;
+mod profile;
+mod state;
+mod superopt;
 
-pub use cpu::{Cpu, Flag};
+pub use access::{Access, AccessMap};
+pub use assembler::{AsmError, Assembly, assemble};
+pub use coverage::Coverage;
+pub use cpu::{Cpu, Flag, Stop};
+pub use detector::Loop;
+pub use devices::{Device, Lcd, Rng, Timer, Uart};
+pub use explore::{Exploration, Explorer, Input};
 pub use instructions::I;
+pub use interrupts::Interrupts;
+pub use isa::Isa;
+pub use memory::{Fill, MemoryMap, Policy, Violation};
+pub use profile::{Block, Profile};
+pub use superopt::{Case, Cost, Solution, Spec, Superoptimizer};
+use cpu::supports;
+use detector::LoopDetector;
 use instructions::{IBuilder, Instruction};
-pub use disassemble::{Disassembly, disassemble};
+use state::State;
+pub use disassemble::{Disassembly, disassemble, disassemble_from, disassemble_traced};
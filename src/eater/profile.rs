@@ -0,0 +1,211 @@
+use super::{disassemble_from, Access, Disassembly, I, Isa};
+
+/// Counts how often each instruction was executed and the cycles it took, and how often each
+/// address was read and written, while executing a program.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    executions: [u64; 256],
+    cycles: [u64; 256],
+    reads: [u64; 256],
+    writes: [u64; 256],
+}
+
+/// A run of instructions that always execute together: control only enters at the first and only
+/// leaves after the last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block {
+    /// The address of the first instruction.
+    pub start: u8,
+    /// The address of the last instruction.
+    pub end: u8,
+    /// How often the first instruction was executed.
+    pub executions: u64,
+    /// The cycles taken by all of the block's instructions.
+    pub cycles: u64,
+}
+
+impl Profile {
+    /// Returns how often the instruction at the given address was executed.
+    /// ```
+    /// use busyboard::eater::{Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x09),
+    ///     I::sub(0x0a),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x03, 0x01]).with_profiler();
+    ///
+    /// cpu.run();
+    /// let profile = cpu.profile().unwrap();
+    /// assert_eq!(profile.executions(0x00), 1);
+    /// assert_eq!(profile.executions(0x02), 3);
+    /// assert_eq!(profile.reads(0x0a), 3);
+    /// ```
+    pub fn executions(&self, adr: u8) -> u64 {
+        self.executions[adr as usize]
+    }
+
+    /// Returns the cycles taken by the instruction at the given address, over all its executions.
+    pub fn cycles(&self, adr: u8) -> u64 {
+        self.cycles[adr as usize]
+    }
+
+    /// Returns how often `Lda`, `Add` or `Sub` read the given address.
+    pub fn reads(&self, adr: u8) -> u64 {
+        self.reads[adr as usize]
+    }
+
+    /// Returns how often `Sta` wrote the given address.
+    pub fn writes(&self, adr: u8) -> u64 {
+        self.writes[adr as usize]
+    }
+
+    /// Returns how often the given address was executed, read or written.
+    pub fn touches(&self, adr: u8) -> u64 {
+        self.executions(adr) + self.reads(adr) + self.writes(adr)
+    }
+
+    /// Returns the addresses of the instructions executed, most often first, with their counts.
+    /// Ties go to the lower address.
+    pub fn hottest(&self) -> Vec<(u8, u64)> {
+        let mut hottest = (0..=255).map(|adr| (adr, self.executions(adr)))
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+        hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest
+    }
+
+    /// Split the disassembled instructions into basic blocks, in address order, with the cycles each
+    /// took. A block starts at address 0, at a jump or call target, after a jump or call, and after
+    /// `Hlt`, `Rti` or `Ret`.
+    /// ```
+    /// use busyboard::eater::{disassemble, Block, Cpu, I};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::lda(0x09),
+    ///     I::sub(0x0a),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x03, 0x01]).with_profiler();
+    ///
+    /// cpu.run();
+    /// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+    /// let blocks = cpu.profile().unwrap().blocks(&disassemble(&bytes));
+    /// assert_eq!(blocks[0], Block { start: 0x00, end: 0x00, executions: 1, cycles: 5 });
+    /// assert_eq!(blocks[1], Block { start: 0x02, end: 0x04, executions: 3, cycles: 3 * 6 + 2 * 3 + 4 });
+    /// ```
+    pub fn blocks(&self, disassembly: &[Disassembly]) -> Vec<Block> {
+        let instructions = disassembly.iter().filter_map(|segment| match segment {
+            Disassembly::Instruction { instruction, offset, len } => Some((*offset, *len, instruction)),
+            _ => None,
+        }).collect::<Vec<_>>();
+
+        let mut leaders = [false; 256];
+        leaders[0] = true;
+        for (offset, len, instruction) in &instructions {
+            if let Some(target) = instruction.target() {
+                leaders[target as usize] = true;
+            }
+
+            if let (I::Jmp(..) | I::Jpz(..) | I::Jpc(..) | I::Call(..) | I::Hlt(..) | I::Rti(..) | I::Ret(..), Some(next)) = (instruction, offset.checked_add(*len)) {
+                leaders[next as usize] = true;
+            }
+        }
+
+        let mut blocks: Vec<Block> = vec![];
+        let mut previous_end = None;
+        for (offset, len, _) in instructions {
+            let follows = previous_end == Some(offset);
+            previous_end = offset.checked_add(len);
+
+            match blocks.last_mut() {
+                Some(block) if follows && !leaders[offset as usize] => {
+                    block.end = offset;
+                    block.cycles += self.cycles(offset);
+                },
+                _ => blocks.push(Block {
+                    start: offset,
+                    end: offset,
+                    executions: self.executions(offset),
+                    cycles: self.cycles(offset),
+                }),
+            }
+        }
+
+        blocks
+    }
+
+    /// Forget all counts.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// A text report of the program in `bytes`, decoded with `isa`: the `top` hottest instructions,
+    /// then the cycles spent in each basic block that was executed.
+    /// ```
+    /// use busyboard::eater::{Cpu, I, Isa};
+    /// let mut cpu = Cpu::from_asm(vec![
+    ///     I::ldi(0x02),
+    ///     I::sub(0x09),
+    ///     I::jpz(0x08),
+    ///     I::jmp(0x02),
+    ///     I::hlt(),
+    /// ], vec![0x01]).with_profiler();
+    ///
+    /// cpu.run();
+    /// let bytes = cpu.read_bytes(0, cpu.len()).to_vec();
+    /// let report = cpu.profile().unwrap().report(&bytes, Isa::Basic, false, 1);
+    /// assert!(report.contains("0x02     Sub 0x09"));
+    /// assert!(report.contains("0x02-0x04"));
+    /// ```
+    pub fn report(&self, bytes: &[u8], isa: Isa, interrupts: bool, top: usize) -> String {
+        let disassembly = disassemble_from(bytes, &self.hottest().iter().map(|(adr, _)| *adr).collect::<Vec<_>>(), isa, interrupts);
+        let instructions = disassembly.iter().filter_map(|segment| match segment {
+            Disassembly::Instruction { instruction, offset, .. } => Some((*offset, instruction)),
+            _ => None,
+        }).collect::<Vec<_>>();
+        let total = (0..=255).map(|adr| self.cycles(adr)).sum::<u64>().max(1);
+
+        let mut report = String::from("Hottest instructions\n  executions      cycles  address  instruction\n");
+        for (adr, count) in self.hottest().into_iter().take(top) {
+            let instruction = instructions.iter().find(|(offset, _)| *offset == adr)
+                .map_or(String::from("?"), |(_, instruction)| instruction.to_string());
+            let address = format!("{:#04x}", adr);
+            report.push_str(&format!("  {:>10}  {:>10}  {:<7}  {}\n", count, self.cycles(adr), address, instruction));
+        }
+
+        report.push_str("\nCycles per basic block\n  executions      cycles  addresses  share\n");
+        for block in self.blocks(&disassembly).into_iter().filter(|block| block.executions > 0) {
+            report.push_str(&format!(
+                "  {:>10}  {:>10}  {:#04x}-{:#04x}  {:>5.1}%\n",
+                block.executions,
+                block.cycles,
+                block.start,
+                block.end,
+                block.cycles as f64 * 100.0 / total as f64,
+            ));
+        }
+
+        report
+    }
+
+    pub (super) fn execute(&mut self, adr: u8, cycles: u8) {
+        self.executions[adr as usize] += 1;
+        self.cycles[adr as usize] += cycles as u64;
+    }
+
+    pub (super) fn record(&mut self, adr: u8, access: Access) {
+        match access {
+            Access::Read => self.reads[adr as usize] += 1,
+            Access::Write => self.writes[adr as usize] += 1,
+            Access::Opcode | Access::Operand => {},
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self { executions: [0; 256], cycles: [0; 256], reads: [0; 256], writes: [0; 256] }
+    }
+}
@@ -0,0 +1,77 @@
+use super::{Cpu, Interrupts, Isa, MemoryMap};
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// A copy of everything that determines what the CPU does next, except devices.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub (super) struct State {
+    pub (super) a: u8,
+    pub (super) ip: u8,
+    pub (super) sp: u8,
+    pub (super) flags: u8,
+    pub (super) isa: Isa,
+    pub (super) ram: Vec<u8>,
+    pub (super) initialized: [bool; 256],
+    pub (super) memory: MemoryMap,
+    pub (super) interrupts: Option<Interrupts>,
+}
+
+impl State {
+    pub (super) fn of(cpu: &Cpu) -> Self {
+        Self {
+            a: cpu.a,
+            ip: cpu.ip,
+            sp: cpu.sp,
+            flags: cpu.flags,
+            isa: cpu.isa,
+            ram: cpu.ram.clone(),
+            initialized: cpu.initialized,
+            memory: cpu.memory.clone(),
+            interrupts: cpu.interrupts,
+        }
+    }
+
+    pub (super) fn matches(&self, cpu: &Cpu) -> bool {
+        self.a == cpu.a && self.ip == cpu.ip && self.sp == cpu.sp && self.flags == cpu.flags && self.ram == cpu.ram
+            && self.initialized == cpu.initialized && self.interrupts == cpu.interrupts
+    }
+
+    pub (super) fn store(&mut self, adr: u8, val: u8) {
+        if adr as usize >= self.ram.len() {
+            self.ram.resize(adr as usize + 1, 0);
+        }
+
+        self.ram[adr as usize] = val;
+        self.initialized[adr as usize] = true;
+    }
+
+    /// Execute one instruction, returning the resulting state, the value output, if any, and
+    /// whether `In` was executed. `In` reads 0, and there are no devices, so none requests an
+    /// interrupt.
+    pub (super) fn step(&self) -> (State, Option<u8>, bool) {
+        let out = Rc::new(Cell::new(None));
+        let cpu_out = out.clone();
+        let read = Rc::new(Cell::new(false));
+        let cpu_read = read.clone();
+
+        let mut cpu = Cpu::from_asm(vec![], vec![])
+            .with_memory_map(self.memory.clone())
+            .with_isa(self.isa)
+            .with_in(move || {
+                cpu_read.set(true);
+                0
+            })
+            .with_out(move |value| cpu_out.set(Some(value)));
+        cpu.a = self.a;
+        cpu.ip = self.ip;
+        cpu.sp = self.sp;
+        cpu.flags = self.flags;
+        cpu.ram = self.ram.clone();
+        cpu.initialized = self.initialized;
+        cpu.interrupts = self.interrupts;
+
+        cpu.step();
+
+        (State::of(&cpu), out.get(), read.get())
+    }
+}
@@ -0,0 +1,330 @@
+use super::{Cpu, Flag, I, Instruction};
+
+/// The behavior a program must have: for each case, the RAM contents before running and what is
+/// expected once the program halts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Spec {
+    pub cases: Vec<Case>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Case {
+    pub input: Vec<(u8, u8)>,
+    pub a: Option<u8>,
+    pub out: Option<Vec<u8>>,
+    pub memory: Vec<(u8, u8)>,
+}
+
+/// What makes one program better than another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cost {
+    /// The fewest instructions, then the fewest bytes.
+    Size,
+    /// The fewest instructions executed across all cases.
+    Steps,
+    /// The fewest clock cycles taken across all cases.
+    Cycles,
+}
+
+pub struct Superoptimizer {
+    spec: Spec,
+    cost: Cost,
+    max_len: usize,
+    step_limit: usize,
+}
+
+/// A program satisfying a `Spec`. The program is followed by an implicit `Hlt`.
+pub struct Solution {
+    pub program: Vec<I>,
+    pub bytes: usize,
+    pub steps: usize,
+    pub cycles: u64,
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Out,
+    Ldi(u8),
+    Lda(u8),
+    Sta(u8),
+    Add(u8),
+    Sub(u8),
+    Jmp(usize),
+    Jpz(usize),
+    Jpc(usize),
+}
+
+impl Spec {
+    /// Parse a specification with one case per line. Inputs are written as `[address]=value`
+    /// before `->`, and expectations as `a=value`, `out=value,...` or `[address]=value` after it.
+    /// Numbers are decimal or `0x` prefixed hexadecimal, and `#` starts a comment.
+    /// ```
+    /// use busyboard::eater::{Case, Spec};
+    /// let spec = Spec::parse("
+    ///     [0x0e]=3 [0x0f]=4 -> a=7 out=7 # Add two numbers
+    /// ").unwrap();
+    ///
+    /// assert_eq!(spec.cases, vec![Case {
+    ///     input: vec![(0x0e, 3), (0x0f, 4)],
+    ///     a: Some(7),
+    ///     out: Some(vec![7]),
+    ///     memory: vec![],
+    /// }]);
+    /// assert!(Spec::parse("[0x0e]=3 a=7").is_err());
+    /// ```
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut cases = vec![];
+
+        for (n, line) in spec.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| format!("line {}: {}", n + 1, message);
+            let (input, expected) = line.split_once("->").ok_or_else(|| error("expected `->`".to_string()))?;
+            let mut case = Case::default();
+
+            for token in input.split_whitespace() {
+                case.input.push(parse_memory(token).map_err(error)?);
+            }
+
+            for token in expected.split_whitespace() {
+                if let Some(value) = token.strip_prefix("a=") {
+                    case.a = Some(parse_number(value).map_err(error)?);
+                } else if let Some(values) = token.strip_prefix("out=") {
+                    let values = values.split(',').filter(|v| !v.is_empty()).map(parse_number);
+                    case.out = Some(values.collect::<Result<_, _>>().map_err(error)?);
+                } else {
+                    case.memory.push(parse_memory(token).map_err(error)?);
+                }
+            }
+
+            cases.push(case);
+        }
+
+        Ok(Self { cases })
+    }
+
+    fn addresses(&self) -> Vec<u8> {
+        let mut addresses = self.cases.iter()
+            .flat_map(|case| case.input.iter().chain(&case.memory).map(|(adr, _)| *adr))
+            .collect::<Vec<_>>();
+
+        addresses.sort();
+        addresses.dedup();
+        addresses
+    }
+
+    fn constants(&self) -> Vec<u8> {
+        let mut constants = vec![0x00, 0x01, 0xff];
+        for case in &self.cases {
+            constants.extend(case.input.iter().chain(&case.memory).map(|(_, value)| *value));
+            constants.extend(case.a);
+            constants.extend(case.out.iter().flatten());
+        }
+
+        constants.sort();
+        constants.dedup();
+        constants
+    }
+}
+
+impl Superoptimizer {
+    pub fn new(spec: Spec) -> Self {
+        Self { spec, cost: Cost::Size, max_len: 4, step_limit: 256 }
+    }
+
+    pub fn with_cost(mut self, cost: Cost) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Search programs of up to the given number of instructions.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Give up on a case after executing the given number of instructions.
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Enumerate candidate programs, verifying each by running it on a `Cpu`.
+    ///
+    /// Operands are drawn from the addresses and values in the spec, and jumps may target any
+    /// instruction in the candidate. Programs that would overlap an address in the spec are skipped.
+    /// ```
+    /// use busyboard::eater::{Spec, Superoptimizer};
+    /// // Double a number
+    /// let spec = Spec::parse("
+    ///     [0x0f]=3 -> a=6
+    ///     [0x0f]=10 -> a=20
+    /// ").unwrap();
+    ///
+    /// let solution = Superoptimizer::new(spec).with_max_len(2).search().unwrap();
+    /// let program = solution.program.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+    /// assert_eq!(program, vec!["Lda 0x0f", "Add 0x0f"]);
+    /// assert_eq!(solution.steps, 6);
+    /// assert_eq!(solution.cycles, 2 * (5 + 6 + 3));
+    ///
+    /// // Leave memory as it is, which the implicit `Hlt` alone does
+    /// let spec = Spec::parse("[0x0f]=5 -> [0x0f]=5").unwrap();
+    /// let solution = Superoptimizer::new(spec).search().unwrap();
+    /// assert!(solution.program.is_empty());
+    /// assert_eq!(solution.bytes, 1);
+    /// ```
+    pub fn search(&self) -> Option<Solution> {
+        let addresses = self.spec.addresses();
+        let constants = self.spec.constants();
+        let limit = addresses.first().map_or(256, |adr| *adr as usize);
+
+        let mut best: Option<Solution> = None;
+        for len in 0..=self.max_len {
+            let mut alphabet = vec![Op::Out];
+            alphabet.extend(constants.iter().map(|v| Op::Ldi(*v)));
+            for adr in &addresses {
+                alphabet.extend([Op::Lda(*adr), Op::Sta(*adr), Op::Add(*adr), Op::Sub(*adr)]);
+            }
+            for target in 0..=len {
+                alphabet.extend([Op::Jmp(target), Op::Jpz(target), Op::Jpc(target)]);
+            }
+
+            let mut indices = vec![0; len];
+            loop {
+                let ops = indices.iter().map(|i| alphabet[*i]).collect::<Vec<_>>();
+                let bytes = assemble(&ops);
+
+                if bytes.len() <= limit {
+                    if let Some((steps, cycles)) = self.verify(&bytes) {
+                        let better = match &best {
+                            None => true,
+                            Some(best) => match self.cost {
+                                Cost::Size => bytes.len() < best.bytes,
+                                Cost::Steps => steps < best.steps,
+                                Cost::Cycles => cycles < best.cycles,
+                            },
+                        };
+
+                        if better {
+                            best = Some(Solution { program: program(&ops), bytes: bytes.len(), steps, cycles });
+                        }
+                    }
+                }
+
+                if !next(&mut indices, alphabet.len()) {
+                    break;
+                }
+            }
+
+            if best.is_some() && self.cost == Cost::Size {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Run the program against every case, returning the total number of instructions executed and
+    /// the cycles they took.
+    fn verify(&self, bytes: &[u8]) -> Option<(usize, u64)> {
+        let mut total = 0;
+        let mut cycles = 0;
+
+        for case in &self.spec.cases {
+            let out = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+            let cpu_out = out.clone();
+            let mut cpu = Cpu::from_asm(vec![], bytes.to_vec())
+                .with_out(move |value| cpu_out.borrow_mut().push(value));
+
+            for (adr, value) in &case.input {
+                cpu.write(*adr, *value);
+            }
+            // Expected outputs start out zeroed, rather than past the end of RAM.
+            for (adr, _) in &case.memory {
+                if cpu.read(*adr).is_none() {
+                    cpu.write(*adr, 0);
+                }
+            }
+
+            let mut steps = 0;
+            while !cpu.get(Flag::Halt) {
+                if cpu.get(Flag::IllegalHalt) || steps >= self.step_limit {
+                    return None;
+                }
+
+                cpu.step();
+                steps += 1;
+            }
+
+            let matches = case.a.is_none_or(|a| cpu.a() == a)
+                && case.out.as_ref().is_none_or(|expected| *out.borrow() == *expected)
+                && case.memory.iter().all(|(adr, value)| cpu.read(*adr) == Some(*value));
+            if !matches {
+                return None;
+            }
+
+            total += steps;
+            cycles += cpu.cycles();
+        }
+
+        Some((total, cycles))
+    }
+}
+
+fn assemble(ops: &[Op]) -> Vec<u8> {
+    program(ops).iter().chain(&[I::hlt()]).flat_map(|i| i.assemble()).collect()
+}
+
+fn program(ops: &[Op]) -> Vec<I> {
+    let mut offsets = vec![0_u8];
+    for op in ops {
+        let size = if let Op::Out = op { 1 } else { 2 };
+        offsets.push(offsets.last().unwrap() + size);
+    }
+
+    ops.iter().map(|op| match *op {
+        Op::Out => I::out(),
+        Op::Ldi(value) => I::ldi(value),
+        Op::Lda(adr) => I::lda(adr),
+        Op::Sta(adr) => I::sta(adr),
+        Op::Add(adr) => I::add(adr),
+        Op::Sub(adr) => I::sub(adr),
+        Op::Jmp(target) => I::jmp(offsets[target]),
+        Op::Jpz(target) => I::jpz(offsets[target]),
+        Op::Jpc(target) => I::jpc(offsets[target]),
+    }).collect()
+}
+
+/// Advance to the next candidate, returning false once all have been tried.
+fn next(indices: &mut [usize], n: usize) -> bool {
+    for i in indices.iter_mut().rev() {
+        *i += 1;
+        if *i < n {
+            return true;
+        }
+
+        *i = 0;
+    }
+
+    false
+}
+
+fn parse_memory(token: &str) -> Result<(u8, u8), String> {
+    let (adr, value) = token.split_once('=').ok_or_else(|| format!("expected `[address]=value`, found `{}`", token))?;
+    let adr = adr.strip_prefix('[').and_then(|adr| adr.strip_suffix(']'))
+        .ok_or_else(|| format!("expected `[address]=value`, found `{}`", token))?;
+
+    Ok((parse_number(adr)?, parse_number(value)?))
+}
+
+fn parse_number(n: &str) -> Result<u8, String> {
+    let parsed = match n.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => n.parse(),
+    };
+
+    parsed.map_err(|_| format!("invalid byte `{}`", n))
+}
@@ -1,3 +1,4 @@
+pub mod config;
 pub mod eater;
 pub mod ui;
 pub mod simulator;
@@ -1,10 +1,88 @@
 use busyboard::{
-    eater::{Cpu, I},
-    simulator::Simulator,
+    config::Config,
+    eater::{assemble, Cost, Cpu, I, Interrupts, Isa, MemoryMap, Spec, Stop, Superoptimizer},
+    simulator::{Devices, Keymap, Simulator, Speed, Theme},
     ui::Ui,
 };
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::rc::Rc;
 
 fn main() -> std::io::Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("superopt") => superopt(&args[1..]),
+        Some("run") => run(&args[1..]),
+        _ => simulate(&args),
+    }
+}
+
+/// Usage: busyboard [--config PATH] [--hz N|max] [--out-limit N] [--theme NAME] [program.asm]
+fn simulate(args: &[String]) -> std::io::Result<()> {
+    let mut config = None;
+    let mut speed = Speed::Hz(1.0);
+    let mut limit = None;
+    let mut theme = None;
+    let mut path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config = match args.next() {
+                Some(path) => Some(path.into()),
+                None => usage("--config expects a path"),
+            },
+            "--hz" => speed = match args.next().map(|hz| hz.parse()) {
+                Some(Ok(hz)) => hz,
+                Some(Err(error)) => usage(&error),
+                None => usage("--hz expects a frequency"),
+            },
+            "--theme" => theme = match args.next() {
+                Some(name) => match Theme::named(name) {
+                    Some(theme) => Some(theme),
+                    None => usage(&format!("unknown theme `{}`, expected one of {}", name, Theme::NAMES.join(", "))),
+                },
+                None => usage("--theme expects a name"),
+            },
+            "--out-limit" => limit = match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => Some(n),
+                None => usage("--out-limit expects a number"),
+            },
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(&format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    let config = Config::load(config).unwrap_or_else(|error| fail(&error));
+    let keymap = Keymap::from(&config).unwrap_or_else(|error| fail(&format!("keys: {}", error)));
+    let devices = Devices::from(&config).unwrap_or_else(|error| fail(&format!("devices: {}", error)));
+    let theme = theme.unwrap_or_else(|| Theme::from(&config).unwrap_or_else(|error| fail(&error)));
+
+    let simulator = match path {
+        Some(path) => edit(path, &config)?,
+        None => count(&config),
+    };
+    let simulator = match limit {
+        Some(limit) => simulator.with_output_limit(limit),
+        None => simulator,
+    };
+    Ui::new().run(simulator.with_keymap(keymap).with_theme(theme).with_devices(devices).with_speed(speed))
+}
+
+fn edit(path: &str, config: &Config) -> std::io::Result<Simulator> {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error),
+    };
+
+    let cpu = machine(Cpu::from_asm(vec![], vec![]), config);
+    Ok(Simulator::from(cpu).with_source(&source, Some(path.into())))
+}
+
+fn count(config: &Config) -> Simulator {
     // Count to 100 and then halt
     let cpu = Cpu::from_asm(vec![
         I::lda(15),
@@ -17,6 +95,186 @@ fn main() -> std::io::Result<()> {
         I::hlt()
     ], vec![0x01, 0x00, 100]);
 
-    let simulator = Simulator::from(cpu);
-    Ui::new().run(simulator)
+    Simulator::from(machine(cpu, config))
+}
+
+/// Give the CPU the memory map, instruction set and interrupts of the config file, and detect loops.
+fn machine(cpu: Cpu, config: &Config) -> Cpu {
+    let memory = MemoryMap::from(config).unwrap_or_else(|error| fail(&format!("memory: {}", error)));
+    let isa = Isa::from(config).unwrap_or_else(|error| fail(&format!("machine: {}", error)));
+    let interrupts = Interrupts::from(config).unwrap_or_else(|error| fail(&format!("interrupts: {}", error)));
+    let cpu = cpu.with_memory_map(memory).with_isa(isa).with_loop_detection();
+
+    match interrupts {
+        Some(interrupts) => cpu.with_interrupts(interrupts),
+        None => cpu,
+    }
+}
+
+/// Usage: busyboard superopt [--steps|--cycles] [--max-len N] <spec>
+fn superopt(args: &[String]) -> std::io::Result<()> {
+    let mut cost = Cost::Size;
+    let mut max_len = 4;
+    let mut path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => cost = Cost::Steps,
+            "--cycles" => cost = Cost::Cycles,
+            "--max-len" => max_len = match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => n,
+                None => usage("--max-len expects a number"),
+            },
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(&format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    let Some(path) = path else { usage("missing spec") };
+    let spec = match Spec::parse(&std::fs::read_to_string(path)?) {
+        Ok(spec) => spec,
+        Err(error) => usage(&format!("{}: {}", path, error)),
+    };
+
+    match Superoptimizer::new(spec).with_cost(cost).with_max_len(max_len).search() {
+        Some(solution) => {
+            for i in solution.program.iter().chain(&[I::hlt()]) {
+                println!("{}", i);
+            }
+            println!("# {} bytes, {} steps, {} cycles", solution.bytes, solution.steps, solution.cycles);
+        },
+        None => println!("# No program of up to {} instructions satisfies the spec", max_len),
+    }
+
+    Ok(())
+}
+
+/// Usage: busyboard run [--config PATH] [--input PATH] [--uart PATH] [--profile PATH]
+///                      [--coverage PATH] [--lcov PATH] <program.asm>
+///
+/// Runs the program without the TUI, printing each value output on a line of its own. `In` reads
+/// the next byte of the input, stdin unless a path is given, and 0 once it runs out. The memory
+/// map, instruction set, interrupts and devices come from the config file, and the UART receives
+/// the same input and sends to the `--uart` path, or stderr. With `--profile`, a report of the
+/// hottest instructions and the cycles per basic block is written to the path, however the program
+/// stops. `--coverage` writes which instructions and branch directions were missed, then the
+/// annotated disassembly, and `--lcov` writes the same as an LCOV tracefile for the program's
+/// source.
+fn run(args: &[String]) -> std::io::Result<()> {
+    let mut config = None;
+    let mut input = None;
+    let mut uart = None;
+    let mut path = None;
+    let mut profile = None;
+    let mut coverage = None;
+    let mut lcov = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config = match args.next() {
+                Some(path) => Some(path.into()),
+                None => usage("--config expects a path"),
+            },
+            "--input" => input = match args.next() {
+                Some(path) => Some(path),
+                None => usage("--input expects a path"),
+            },
+            "--uart" => uart = match args.next() {
+                Some(path) => Some(path),
+                None => usage("--uart expects a path"),
+            },
+            "--profile" => profile = match args.next() {
+                Some(path) => Some(path),
+                None => usage("--profile expects a path"),
+            },
+            "--coverage" => coverage = match args.next() {
+                Some(path) => Some(path),
+                None => usage("--coverage expects a path"),
+            },
+            "--lcov" => lcov = match args.next() {
+                Some(path) => Some(path),
+                None => usage("--lcov expects a path"),
+            },
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(&format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    let Some(path) = path else { usage("missing program") };
+    let config = Config::load(config).unwrap_or_else(|error| fail(&error));
+    let source = std::fs::read_to_string(path)?;
+    let devices = Devices::from(&config).unwrap_or_else(|error| fail(&format!("devices: {}", error)));
+
+    let input: Box<dyn BufRead> = match input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let input = Rc::new(RefCell::new(input.bytes()));
+    let received = input.clone();
+    let mut sent: Box<dyn Write> = match uart {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stderr()),
+    };
+
+    let (cpu, _) = devices.attach(
+        machine(Cpu::from_asm(vec![], vec![]), &config),
+        move || received.borrow_mut().next().and_then(Result::ok),
+        move |byte| {
+            let _ = sent.write_all(&[byte]).and_then(|_| sent.flush());
+        },
+    );
+    let mut cpu = cpu
+        .with_profiler()
+        .with_coverage()
+        .with_in(move || input.borrow_mut().next().and_then(Result::ok).unwrap_or(0))
+        .with_out(|value| println!("{}", value));
+
+    let assembly = match assemble(&source, cpu.isa(), cpu.interrupts().is_some()) {
+        Ok(assembly) => assembly,
+        Err(errors) => {
+            let errors = errors.iter().map(|error| format!("{}:{}: {}", path, error.line + 1, error.message));
+            fail(&errors.collect::<Vec<_>>().join("\n"))
+        },
+    };
+
+    cpu.load(assembly.bytes());
+
+    let stop = cpu.run();
+    for warning in cpu.warnings() {
+        eprintln!("warning: {}", warning);
+    }
+
+    if let (Some(path), Some(report)) = (profile, cpu.profile()) {
+        std::fs::write(path, report.report(cpu.read_bytes(0, cpu.len()), cpu.isa(), cpu.interrupts().is_some(), 10))?;
+    }
+    if let (Some(path), Some(report)) = (coverage, cpu.coverage()) {
+        let bytes = cpu.read_bytes(0, cpu.len());
+        std::fs::write(path, format!("{}\n{}", report.report(bytes, cpu.isa(), cpu.interrupts().is_some()), report.annotate(bytes, cpu.isa(), cpu.interrupts().is_some())))?;
+    }
+    if let (Some(lcov), Some(report)) = (lcov, cpu.coverage()) {
+        std::fs::write(lcov, report.lcov(&assembly, cpu.isa(), cpu.interrupts().is_some(), path))?;
+    }
+
+    match stop {
+        Stop::Halt => Ok(()),
+        Stop::IllegalHalt => fail(&format!("illegal halt at {:#04x}", cpu.ip())),
+        Stop::Loop(detected) => fail(&detected.to_string()),
+        Stop::Fault(violation) => fail(&format!("{} by the instruction at {:#04x}", violation, cpu.ip())),
+    }
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("error: {}", error);
+    eprintln!("usage: busyboard [--config PATH] [--hz N|max] [--out-limit N] [--theme NAME] [program.asm]");
+    eprintln!("       busyboard run [--config PATH] [--input PATH] [--uart PATH] [--profile PATH]");
+    eprintln!("                     [--coverage PATH] [--lcov PATH] <program.asm>");
+    eprintln!("       busyboard superopt [--steps|--cycles] [--max-len N] <spec>");
+    std::process::exit(2);
+}
+
+fn fail(error: &str) -> ! {
+    eprintln!("error: {}", error);
+    std::process::exit(1);
 }
@@ -0,0 +1,181 @@
+use std::time::{Duration, Instant};
+
+/// How often the screen is redrawn while executing, regardless of the clock speed.
+pub const FRAME: Duration = Duration::from_millis(33);
+
+/// How long to wait for input when nothing is executing.
+const IDLE: Duration = Duration::from_millis(1000);
+
+/// The speeds the clock steps through, in Hz, before running as fast as possible.
+const SPEEDS: [f64; 12] = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+
+/// The clock speed of the simulated CPU.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    Hz(f64),
+    Max,
+}
+
+impl Speed {
+    /// The next speed up, or `Max` when there is none.
+    /// ```
+    /// use busyboard::simulator::Speed;
+    /// assert_eq!(Speed::Hz(1.0).faster(), Speed::Hz(2.0));
+    /// assert_eq!(Speed::Hz(3.0).faster(), Speed::Hz(5.0));
+    /// assert_eq!(Speed::Hz(1_000_000.0).faster(), Speed::Max);
+    /// ```
+    pub fn faster(self) -> Self {
+        match self {
+            Speed::Hz(hz) => SPEEDS.into_iter().find(|&speed| speed > hz).map_or(Speed::Max, Speed::Hz),
+            Speed::Max => Speed::Max,
+        }
+    }
+
+    /// The next speed down, stopping at the slowest.
+    /// ```
+    /// use busyboard::simulator::Speed;
+    /// assert_eq!(Speed::Max.slower(), Speed::Hz(1_000_000.0));
+    /// assert_eq!(Speed::Hz(3.0).slower(), Speed::Hz(2.0));
+    /// assert_eq!(Speed::Hz(0.5).slower(), Speed::Hz(0.5));
+    /// ```
+    pub fn slower(self) -> Self {
+        match self {
+            Speed::Hz(hz) => Speed::Hz(SPEEDS.into_iter().rev().find(|&speed| speed < hz).unwrap_or(SPEEDS[0])),
+            Speed::Max => Speed::Hz(SPEEDS[SPEEDS.len() - 1]),
+        }
+    }
+}
+
+/// Parses a frequency in Hz, or `max`.
+/// ```
+/// use busyboard::simulator::Speed;
+/// assert_eq!("max".parse(), Ok(Speed::Max));
+/// assert_eq!("0.5".parse(), Ok(Speed::Hz(0.5)));
+/// assert!("0".parse::<Speed>().is_err());
+/// ```
+impl std::str::FromStr for Speed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "max" => Ok(Speed::Max),
+            _ => match s.parse::<f64>() {
+                Ok(hz) if hz.is_finite() && hz > 0.0 => Ok(Speed::Hz(hz)),
+                _ => Err(format!("invalid clock speed `{}`, expected a frequency in Hz or `max`", s)),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Speed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Speed::Hz(hz) => write!(f, "{}Hz", si(*hz)),
+            Speed::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// Decides how many instructions to execute each time the simulator wakes up, so the CPU runs at
+/// the chosen speed however often the screen is redrawn, and measures how fast it actually ran.
+pub struct Clock {
+    speed: Speed,
+    last: Instant,
+    owed: f64,
+    window: Instant,
+    executed: u64,
+    ips: f64,
+}
+
+impl Clock {
+    pub fn new(speed: Speed) -> Self {
+        Self {
+            speed,
+            last: Instant::now(),
+            owed: 0.0,
+            window: Instant::now(),
+            executed: 0,
+            ips: 0.0,
+        }
+    }
+
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.speed = speed;
+        self.start();
+    }
+
+    /// Start counting from now, so time spent paused is not owed as a burst of steps.
+    pub fn start(&mut self) {
+        self.last = Instant::now();
+        self.owed = 0.0;
+        self.window = Instant::now();
+        self.executed = 0;
+        self.ips = 0.0;
+    }
+
+    /// How long until `due` has a step to execute, but at least until the next frame is due.
+    pub fn deadline(&self, running: bool) -> Duration {
+        if !running {
+            return IDLE;
+        }
+
+        let elapsed = self.last.elapsed();
+        match self.speed {
+            Speed::Hz(hz) => {
+                let next_step = Duration::from_secs_f64(((1.0 - self.owed) / hz).max(0.0)).saturating_sub(elapsed);
+                next_step.max(FRAME.saturating_sub(elapsed))
+            },
+            Speed::Max => Duration::ZERO,
+        }
+    }
+
+    /// Returns the number of steps owed since the last call, or `None` when running as fast as
+    /// possible, in which case the caller should step for up to a `FRAME`.
+    pub fn due(&mut self) -> Option<u64> {
+        let elapsed = self.last.elapsed();
+        self.last = Instant::now();
+
+        match self.speed {
+            Speed::Hz(hz) => {
+                self.owed += elapsed.as_secs_f64() * hz;
+                let steps = self.owed.floor();
+                self.owed -= steps;
+                Some(steps as u64)
+            },
+            Speed::Max => None,
+        }
+    }
+
+    /// Count `steps` towards the measured instructions per second.
+    pub fn record(&mut self, steps: u64) {
+        self.executed += steps;
+
+        let elapsed = self.window.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.ips = self.executed as f64 / elapsed.as_secs_f64();
+            self.window = Instant::now();
+            self.executed = 0;
+        }
+    }
+
+    /// Instructions executed per second over the last full second.
+    pub fn ips(&self) -> f64 {
+        self.ips
+    }
+}
+
+/// Formats a value with a metric prefix, e.g. 1500 as "1.5k".
+pub fn si(value: f64) -> String {
+    let (value, prefix) = match value {
+        v if v >= 1e6 => (v / 1e6, "M"),
+        v if v >= 1e3 => (v / 1e3, "k"),
+        v => (v, ""),
+    };
+
+    let value = format!("{:.1}", value);
+    format!("{}{}", value.trim_end_matches(".0"), prefix)
+}
@@ -0,0 +1,165 @@
+use super::{input::Input, theme::Theme};
+use crate::config::Config;
+use crate::eater::{Cpu, Lcd, Rng, Timer, Uart};
+use ratatui::{
+    prelude::{Line, Span, Widget},
+    widgets::Paragraph,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// How many bytes the UART keeps to show.
+const CONSOLE_LIMIT: usize = 4096;
+
+/// Where each device is attached, if at all, from the `[devices]` table of the configuration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Devices {
+    uart: Option<u8>,
+    timer: Option<u8>,
+    lcd: Option<u8>,
+    rng: Option<u8>,
+}
+
+/// What the attached devices show.
+#[derive(Default)]
+pub struct Attached {
+    lcd: Option<Lcd>,
+    console: Option<Rc<RefCell<Vec<u8>>>>,
+}
+
+impl Devices {
+    /// The base address of each device, which takes 2 addresses, except for `rng`, which takes 1.
+    /// ```
+    /// use busyboard::{config::Config, simulator::Devices};
+    /// let config = Config::parse("[devices]\nuart = 0xf0\nrng = 0xff").unwrap();
+    /// assert!(Devices::from(&config).is_ok());
+    ///
+    /// let config = Config::parse("[devices]\nlcd = 0xff").unwrap();
+    /// assert!(Devices::from(&config).is_err());
+    /// ```
+    pub fn from(config: &Config) -> Result<Devices, String> {
+        let mut devices = Devices::default();
+
+        for entry in config.table("devices") {
+            let (slot, size) = match entry.key.as_str() {
+                "uart" => (&mut devices.uart, 2),
+                "timer" => (&mut devices.timer, 2),
+                "lcd" => (&mut devices.lcd, 2),
+                "rng" => (&mut devices.rng, 1),
+                key => return Err(format!("line {}: unknown device `{}`", entry.line + 1, key)),
+            };
+
+            *slot = match entry.value.as_integer() {
+                Some(adr) if (0..=256 - size).contains(&adr) => Some(adr as u8),
+                _ => return Err(format!("line {}: `{}` expects an address from 0 to {}", entry.line + 1, entry.key, 256 - size)),
+            };
+        }
+
+        Ok(devices)
+    }
+
+    /// Attach the devices to the CPU, with the UART receiving bytes from `receive`, until it returns
+    /// `None`, and sending them to `send`. Returns the LCD too, if attached, to show it.
+    /// ```
+    /// use busyboard::{config::Config, eater::{Cpu, I}, simulator::Devices};
+    /// let config = Config::parse("[devices]\nuart = 0xf0\nlcd = 0xf2").unwrap();
+    /// let devices = Devices::from(&config).unwrap();
+    ///
+    /// let mut received = "hi".bytes();
+    /// let cpu = Cpu::from_asm(vec![
+    ///     I::lda(0xf0),
+    ///     I::sta(0xf3),
+    ///     I::hlt(),
+    /// ], vec![]);
+    /// let (mut cpu, lcd) = devices.attach(cpu, move || received.next(), |_| {});
+    ///
+    /// cpu.run();
+    /// assert_eq!(lcd.unwrap().lines()[0].trim_end(), "h");
+    /// ```
+    pub fn attach<R, S>(self, mut cpu: Cpu, receive: R, send: S) -> (Cpu, Option<Lcd>)
+    where
+        R: FnMut() -> Option<u8> + 'static,
+        S: FnMut(u8) + 'static,
+    {
+        let mut shown = None;
+
+        if let Some(adr) = self.uart {
+            cpu = cpu.with_device(adr..=adr + 1, Uart::new(receive, send));
+        }
+        if let Some(adr) = self.timer {
+            cpu = cpu.with_device(adr..=adr + 1, Timer::new());
+        }
+        if let Some(adr) = self.lcd {
+            let lcd = Lcd::new();
+            cpu = cpu.with_device(adr..=adr + 1, lcd.clone());
+            shown = Some(lcd);
+        }
+        if let Some(adr) = self.rng {
+            cpu = cpu.with_device(adr..=adr, Rng::new(1));
+        }
+
+        (cpu, shown)
+    }
+
+    /// Attach the devices to the CPU to show in the simulator. The UART receives the bytes typed in
+    /// the input pane.
+    pub (super) fn connect(self, cpu: Cpu, input: &Rc<RefCell<Input>>) -> (Cpu, Attached) {
+        let input = input.clone();
+        let console = Rc::new(RefCell::new(vec![]));
+        let sent = console.clone();
+
+        let (cpu, lcd) = self.attach(cpu, move || input.borrow_mut().pop(), move |byte| {
+            let mut sent = sent.borrow_mut();
+            if sent.len() == CONSOLE_LIMIT {
+                sent.remove(0);
+            }
+            sent.push(byte);
+        });
+
+        (cpu, Attached { lcd, console: self.uart.map(|_| console) })
+    }
+}
+
+impl Attached {
+    /// The LCD's 2 lines and the console's last line, plus bottom padding, or 0 with neither.
+    pub fn height(&self) -> u16 {
+        match (&self.lcd, &self.console) {
+            (None, None) => 0,
+            (lcd, console) => lcd.as_ref().map_or(0, |_| 2) + console.as_ref().map_or(0, |_| 1) + 1,
+        }
+    }
+
+    /// Show the LCD's text and the end of the last line sent to the console.
+    pub fn render(&self, width: u16, theme: &Theme) -> impl Widget {
+        let mut lines = vec![];
+
+        if let Some(lcd) = &self.lcd {
+            for (i, text) in lcd.lines().into_iter().enumerate() {
+                lines.push(Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled(if i == 0 { "LCD:  " } else { "      " }, theme.heading),
+                    Span::styled("▕", theme.muted),
+                    Span::styled(text, theme.display),
+                    Span::styled("▏", theme.muted),
+                ]));
+            }
+        }
+
+        if let Some(console) = &self.console {
+            let console = console.borrow();
+            let last = console.rsplit(|&byte| byte == b'\n').next().unwrap_or(&[]);
+            let text = last.iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect::<Vec<_>>();
+            let shown = text.len().min(width.saturating_sub(8) as usize); // Heading and padding
+
+            lines.push(Line::from(vec![
+                Span::raw(" "),
+                Span::styled("UART: ", theme.heading),
+                Span::raw(text[text.len() - shown..].iter().collect::<String>()),
+            ]));
+        }
+
+        Paragraph::new(lines)
+    }
+}
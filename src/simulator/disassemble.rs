@@ -1,7 +1,28 @@
-use crate::eater::{Disassembly, I};
+use super::{clock, source::SourceEditor, theme::Theme};
+use crate::eater::{AccessMap, Disassembly, I, Profile};
 use ratatui::prelude::{Line, Span, Stylize};
+use std::collections::BTreeSet;
+
+#[allow(clippy::too_many_arguments)]
+pub fn disassemble<'a>(
+    disassembly: &'a [Disassembly],
+    ip: u8,
+    bytes: &'a [u8],
+    previous_bytes: &'a [u8],
+    accesses: Option<&AccessMap>,
+    profile: Option<&Profile>,
+    source: &'a SourceEditor,
+    breakpoints: &BTreeSet<u8>,
+    theme: &Theme,
+) -> Vec<Line<'a>> {
+    let address = |adr: usize, len: usize| {
+        let marker = if (adr..adr + len).any(|adr| breakpoints.contains(&(adr as u8))) { Span::styled("●", theme.error) } else { " ".into() };
+        [Span::raw(format!("{:02x}:", adr)), marker]
+    };
+    let modified = |adr: usize, len: usize| accesses.is_some_and(|accesses| {
+        (adr..adr + len).any(|adr| accesses.is_self_modified(adr as u8))
+    }).then(|| Span::styled(" Modified", theme.error));
 
-pub fn disassemble<'a>(disassembly: &'a [Disassembly], ip: u8, bytes: &'a [u8], previous_bytes: &'a [u8]) -> Vec<Line<'a>> {
     let mut lines = vec![];
 
     for segment in disassembly {
@@ -11,41 +32,68 @@ pub fn disassemble<'a>(disassembly: &'a [Disassembly], ip: u8, bytes: &'a [u8],
         match segment {
             Disassembly::Data { data, .. } => {
                 for i in (0..data.len()).step_by(2) {
-                    let mut line = vec![Span::raw(format!("{:02x}: ", offset + i))];
+                    let mut line = address(offset + i, 2.min(data.len() - i)).to_vec();
 
                     let n = format!("{:02x}", data[i]);
-                    let n = if offset + i == ip { n.magenta().bold().underlined() } else { n.into() };
-                    let n = if has_changed(data, i, previous_bytes, offset + i) { n.green() } else { n.into() };
+                    let n = if offset + i == ip { Span::styled(n, theme.ip) } else { n.into() };
+                    let n = if has_changed(data, i, previous_bytes, offset + i) { n.patch_style(theme.changed) } else { n };
                     line.push(n);
 
                     line.push(Span::raw(" "));
 
                     if i + 1 < data.len() {
                         let n = format!("{:02x}", data[i + 1]);
-                        let n = if offset + i + 1 == ip { n.magenta().bold().underlined() } else { n.into() };
-                        let n = if has_changed(data, i + 1, previous_bytes, offset + i + 1) { n.green() } else { n.into() };
+                        let n = if offset + i + 1 == ip { Span::styled(n, theme.ip) } else { n.into() };
+                        let n = if has_changed(data, i + 1, previous_bytes, offset + i + 1) { n.patch_style(theme.changed) } else { n };
                         line.push(n);
                     }
 
+                    line.extend(modified(offset + i, 2.min(data.len() - i)));
                     lines.push(Line::from(line));
                 }
             },
+            Disassembly::Variable { value, .. } => {
+                let mut line = address(offset, 1).to_vec();
+                line.extend([Span::styled("Var", theme.heading), Span::raw(" ")]);
+
+                let n = format!("{:02x}", value);
+                let n = if offset == ip { Span::styled(n, theme.ip) } else { n.into() };
+                let n = if has_changed(bytes, offset, previous_bytes, offset) { n.patch_style(theme.changed) } else { n };
+                line.push(n);
+
+                line.extend(modified(offset, 1));
+                lines.push(Line::from(line));
+            },
             Disassembly::Instruction { instruction, .. } => {
-                let mut line = vec![Span::raw(format!("{:02x}: ", offset))];
+                let mut line = address(offset, segment.len() as usize).to_vec();
 
-                let formatted = to_string(&instruction).bold();
-                let formatted = if offset == ip { formatted.magenta().bold().underlined() } else { formatted };
-                let formatted = if has_changed(bytes, offset, previous_bytes, offset) { formatted.green() } else { formatted };
+                let formatted = to_string(instruction).bold();
+                let formatted = if offset == ip { formatted.patch_style(theme.ip) } else { formatted };
+                let formatted = if has_changed(bytes, offset, previous_bytes, offset) { formatted.patch_style(theme.changed) } else { formatted };
                 line.push(formatted);
 
                 line.push(Span::raw(" "));
 
                 if let I::Ldi(..) | I::Lda(..) | I::Sta(..) | I::Add(..) |
-                    I::Sub(..) | I::Jmp(..) | I::Jpz(..) | I::Jpc(..) = instruction {
+                    I::Sub(..) | I::Jmp(..) | I::Jpz(..) | I::Jpc(..) | I::Call(..) = instruction {
                     let data = format!("{:02x}", bytes[offset + 1]);
-                    let data = if offset + 1 == ip { data.magenta().bold().underlined() } else { data.into() };
-                    let data = if has_changed(bytes, offset + 1, previous_bytes, offset + 1) { data.green() } else { data.into() };
+                    let data = if offset + 1 == ip { Span::styled(data, theme.ip) } else { data.into() };
+                    let data = if has_changed(bytes, offset + 1, previous_bytes, offset + 1) { data.patch_style(theme.changed) } else { data };
                     line.push(data);
+                } else {
+                    line.push(Span::raw("  "));
+                }
+
+                if let Some(profile) = profile {
+                    let count = clock::si(profile.executions(offset as u8) as f64);
+                    line.push(Span::styled(format!(" {:>6}", count), theme.muted));
+                }
+
+                line.extend(modified(offset, segment.len() as usize));
+
+                if let Some((n, text)) = source.source_line(offset as u8) {
+                    let text = format!("  {:3}│ {}", n + 1, text);
+                    line.push(if offset == ip { Span::styled(text, theme.current) } else { Span::styled(text, theme.muted) });
                 }
 
                 lines.push(Line::from(line));
@@ -56,8 +104,34 @@ pub fn disassemble<'a>(disassembly: &'a [Disassembly], ip: u8, bytes: &'a [u8],
     lines
 }
 
+/// Returns the index of the line that shows the given address.
+pub fn line_of(disassembly: &[Disassembly], adr: u8) -> usize {
+    let mut line = 0;
+
+    for segment in disassembly {
+        let offset = segment.offset();
+
+        match segment {
+            Disassembly::Data { len, .. } if adr >= offset && adr - offset < *len => return line + (adr - offset) as usize / 2,
+            Disassembly::Data { len, .. } => line += (*len as usize).div_ceil(2),
+            _ if adr >= offset && adr - offset < segment.len() => return line,
+            _ => line += 1,
+        }
+    }
+
+    line
+}
+
+/// Returns the address at the start of each line.
+pub fn addresses(disassembly: &[Disassembly]) -> Vec<u8> {
+    disassembly.iter().flat_map(|segment| match segment {
+        Disassembly::Data { len, offset, .. } => (0..*len).step_by(2).map(|i| offset + i).collect(),
+        _ => vec![segment.offset()],
+    }).collect()
+}
+
 fn to_string(i: &I) -> String {
-    match i {
+    let mnemonic = match i {
         I::Nop(..) => "Nop",
         I::Ldi(..) => "Ldi",
         I::Lda(..) => "Lda",
@@ -67,9 +141,19 @@ fn to_string(i: &I) -> String {
         I::Jmp(..) => "Jmp",
         I::Jpz(..) => "Jpz",
         I::Jpc(..) => "Jpc",
+        I::In(..) => "In",
         I::Out(..) => "Out",
         I::Hlt(..) => "Hlt",
-    }.to_string()
+        I::Rti(..) => "Rti",
+        I::Ei(..) => "Ei",
+        I::Di(..) => "Di",
+        I::Call(..) => "Call",
+        I::Ret(..) => "Ret",
+        I::Push(..) => "Push",
+        I::Pop(..) => "Pop",
+    };
+
+    format!("{:<3}", mnemonic)
 }
 
 fn has_changed(bytes: &[u8], current: usize, previous_bytes: &[u8], previous: usize) -> bool {
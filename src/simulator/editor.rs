@@ -0,0 +1,207 @@
+use crate::eater::Cpu;
+use std::cell::Cell;
+
+/// A hex editor over the CPU's RAM with its own cursor, independent of the IP.
+pub struct MemoryEditor {
+    cursor: u8,
+    anchor: Option<u8>,
+    nibble: Option<u8>,
+    clipboard: Vec<u8>,
+    undo: Vec<(usize, Vec<(u8, u8)>)>,
+    side: Side,
+    columns: Cell<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Ascii,
+    Decimal,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Left,
+    Right,
+    Up,
+    Down,
+    Nibble(u8),
+    Select,
+    Copy,
+    Paste,
+    Insert,
+    Fill,
+    Undo,
+    Side,
+}
+
+impl MemoryEditor {
+    pub fn new() -> Self {
+        Self {
+            cursor: 0,
+            anchor: None,
+            nibble: None,
+            clipboard: vec![],
+            undo: vec![],
+            side: Side::Ascii,
+            columns: Cell::new(16),
+        }
+    }
+
+    pub fn cursor(&self) -> u8 {
+        self.cursor
+    }
+
+    /// Move the cursor to the given address, dropping any selection.
+    pub fn goto(&mut self, adr: u8) {
+        self.cursor = adr;
+        self.anchor = None;
+        self.nibble = None;
+    }
+
+    /// The high nibble typed at the cursor, waiting for the low nibble.
+    pub fn nibble(&self) -> Option<u8> {
+        self.nibble
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Returns true if the address is within the selection.
+    pub fn is_selected(&self, adr: usize) -> bool {
+        self.selection().is_some_and(|(start, end)| start as usize <= adr && adr <= end as usize)
+    }
+
+    /// Remember how many bytes are shown per row, so `Up` and `Down` move by a whole row.
+    pub fn set_columns(&self, columns: usize) {
+        self.columns.set(columns);
+    }
+
+    /// Apply the command at the cursor. `Paste` overwrites the bytes there with the copied ones,
+    /// `Insert` moves them up to make room, growing the RAM, and `Fill` sets the selection to the
+    /// byte where it started. `Undo` reverts the last edit.
+    /// ```
+    /// use busyboard::{eater::Cpu, simulator::editor::{Command, MemoryEditor}};
+    /// let mut cpu = Cpu::from_asm(vec![], vec![0x11, 0x22, 0x33]);
+    /// let mut editor = MemoryEditor::new();
+    ///
+    /// editor.apply(Command::Copy, &mut cpu);
+    /// editor.apply(Command::Right, &mut cpu);
+    /// editor.apply(Command::Insert, &mut cpu);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x11, 0x11, 0x22, 0x33]);
+    ///
+    /// editor.apply(Command::Undo, &mut cpu);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x11, 0x22, 0x33]);
+    ///
+    /// editor.apply(Command::Select, &mut cpu);
+    /// editor.apply(Command::Right, &mut cpu);
+    /// editor.apply(Command::Fill, &mut cpu);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x11, 0x22, 0x22]);
+    ///
+    /// editor.apply(Command::Paste, &mut cpu);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x11, 0x22, 0x11]);
+    ///
+    /// editor.apply(Command::Undo, &mut cpu);
+    /// editor.apply(Command::Undo, &mut cpu);
+    /// assert_eq!(cpu.read_bytes(0, cpu.len()), [0x11, 0x22, 0x33]);
+    /// ```
+    pub fn apply(&mut self, command: Command, cpu: &mut Cpu) {
+        let last = cpu.len().clamp(1, 256) as u8 - 1;
+
+        if !matches!(command, Command::Nibble(..)) {
+            self.nibble = None;
+        }
+
+        match command {
+            Command::Left => self.cursor = self.cursor.saturating_sub(1),
+            Command::Right => self.cursor = self.cursor.saturating_add(1).min(last),
+            Command::Up => self.cursor = self.cursor.saturating_sub(self.columns.get() as u8),
+            Command::Down => self.cursor = self.cursor.saturating_add(self.columns.get() as u8).min(last),
+            Command::Nibble(n) => match self.nibble.take() {
+                Some(high) => {
+                    self.write(cpu, &[(self.cursor, high << 4 | n)]);
+                    self.cursor = self.cursor.saturating_add(1).min(last);
+                },
+                None => self.nibble = Some(n),
+            },
+            Command::Select => self.anchor = match self.anchor {
+                Some(..) => None,
+                None => Some(self.cursor),
+            },
+            Command::Copy => {
+                let (start, end) = self.selection().unwrap_or((self.cursor, self.cursor));
+                self.clipboard = (start..=end).filter_map(|adr| cpu.read(adr)).collect();
+                self.anchor = None;
+            },
+            Command::Paste => {
+                let writes = self.clipboard.iter().enumerate()
+                    .map(|(i, value)| (self.cursor as usize + i, *value))
+                    .filter(|(adr, _)| *adr <= 0xff)
+                    .map(|(adr, value)| (adr as u8, value))
+                    .collect::<Vec<_>>();
+                self.write(cpu, &writes);
+            },
+            Command::Insert => {
+                let inserted = if self.clipboard.is_empty() { vec![0] } else { self.clipboard.clone() };
+                let moved = cpu.read_bytes(self.cursor, cpu.len()).to_vec();
+
+                let writes = inserted.iter().chain(&moved).enumerate()
+                    .map(|(i, value)| (self.cursor as usize + i, *value))
+                    .filter(|(adr, _)| *adr <= 0xff)
+                    .map(|(adr, value)| (adr as u8, value))
+                    .collect::<Vec<_>>();
+                self.write(cpu, &writes);
+            },
+            Command::Fill => if let Some((start, end)) = self.selection() {
+                if let Some(value) = self.anchor.and_then(|anchor| cpu.read(anchor)) {
+                    let writes = (start..=end).map(|adr| (adr, value)).collect::<Vec<_>>();
+                    self.write(cpu, &writes);
+                }
+
+                self.anchor = None;
+            },
+            Command::Undo => if let Some((len, writes)) = self.undo.pop() {
+                for (adr, value) in writes.into_iter().rev() {
+                    cpu.write(adr, value);
+                }
+                cpu.truncate(len);
+            },
+            Command::Side => self.side = match self.side {
+                Side::Ascii => Side::Decimal,
+                Side::Decimal => Side::Ascii,
+            },
+        }
+    }
+
+    fn selection(&self) -> Option<(u8, u8)> {
+        self.anchor.map(|anchor| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// Write the bytes, remembering the previous values and length so the edit can be undone.
+    fn write(&mut self, cpu: &mut Cpu, writes: &[(u8, u8)]) {
+        let previous = writes.iter()
+            .map(|(adr, _)| (*adr, cpu.read(*adr).unwrap_or(0)))
+            .collect();
+
+        self.undo.push((cpu.len(), previous));
+        for (adr, value) in writes {
+            cpu.write(*adr, *value);
+        }
+    }
+}
+
+impl Side {
+    /// The width of the side column for the given number of bytes per row.
+    pub fn width(&self, columns: u16) -> u16 {
+        match self {
+            Side::Ascii => 1 + columns,
+            Side::Decimal => 1 + columns * 4,
+        }
+    }
+}
+
+impl Default for MemoryEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,70 @@
+use super::{keymap::{Keymap, Scope}, theme::Theme};
+use ratatui::{
+    prelude::{Buffer, Line, Rect, Span, Stylize, Widget},
+    widgets::{Block, Clear, Padding, Paragraph},
+};
+
+/// Every binding, grouped by the mode it applies in and followed by the name it is configured by,
+/// scrolled down by `scroll` lines.
+pub fn help(keymap: &Keymap, scroll: u16, theme: &Theme) -> impl Widget {
+    let lines = lines(keymap, theme);
+    let scroll = scroll.min(lines.len().saturating_sub(1) as u16);
+
+    Overlay(Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .block(Block::bordered()
+            .title(" Keys ".bold())
+            .title_bottom(Line::from(" Scroll <↑/↓> Close <any key> ".bold()).centered())
+            .padding(Padding::horizontal(1))
+        ))
+}
+
+fn lines(keymap: &Keymap, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+
+    for scope in Scope::ALL {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(vec![
+            Span::styled(title(scope), theme.current.bold()),
+            Span::styled(format!(" [keys.{}]", scope.name()), theme.muted),
+        ]));
+
+        for (name, description, keys) in keymap.describe(scope) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {:<14} ", keys), theme.key),
+                description.into(),
+                Span::styled(format!(" ({})", name), theme.muted),
+            ]));
+        }
+    }
+
+    lines
+}
+
+fn title(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Execute => "Execute",
+        Scope::Step => "Step",
+        Scope::Seek => "Seek",
+        Scope::Memory => "Memory (type 0-f to edit)",
+        Scope::Source => "Source (type to edit)",
+        Scope::Input => "Input (type 0-f to queue a byte)",
+        Scope::Output => "Output history",
+    }
+}
+
+/// Clears what is beneath the popup before drawing it, centered and at most 72 columns wide.
+struct Overlay<W>(W);
+
+impl<W: Widget> Widget for Overlay<W> {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let width = area.width.min(72);
+        let height = area.height.saturating_sub(4).max(area.height.min(3));
+        let area = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+
+        Clear.render(area, buffer);
+        self.0.render(area, buffer);
+    }
+}
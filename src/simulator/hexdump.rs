@@ -1,28 +1,71 @@
+use super::{editor::{MemoryEditor, Side}, theme::Theme};
+use crate::eater::{Cpu, Profile, Violation};
 use ratatui::{
-    prelude::{Line, Span, Stylize, Widget},
+    prelude::{Line, Rect, Span, Widget},
     widgets::{Block, Padding, Paragraph},
 };
+use std::ops::Range;
 
-pub fn hexdump(ip: u8, bytes: &[u8], previous_bytes: &[u8]) -> impl Widget {
-    let mut lines = vec![Line::from("     0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f")];
+/// Dump the CPU's memory `columns` bytes per line, showing only the lines in `rows`. Read-only
+/// bytes are marked, as are bytes read before they were set, if the memory map reports them. With
+/// `heatmap`, bytes are shaded by how often they were executed, read or written.
+#[allow(clippy::too_many_arguments)]
+pub fn hexdump(
+    cpu: &Cpu,
+    previous_bytes: &[u8],
+    columns: usize,
+    rows: Range<usize>,
+    editor: &MemoryEditor,
+    editing: bool,
+    heatmap: bool,
+    theme: &Theme,
+) -> impl Widget {
+    let (ip, bytes, memory) = (cpu.ip(), cpu.read_bytes(0, cpu.len()), cpu.memory_map());
+    let uninitialized = cpu.warnings().iter().chain(cpu.fault().as_ref())
+        .filter_map(|violation| match violation {
+            Violation::Uninitialized(adr) => Some(*adr as usize),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let profile = cpu.profile().filter(|_| heatmap);
+    let hottest = profile.map_or(0, |profile| (0..=255).map(|adr| profile.touches(adr)).max().unwrap_or(0));
 
-    let mut b = 0;
-    for (i, chunk) in bytes.chunks(16).enumerate() {
-        let mut line = Vec::with_capacity(17);
-        line.push(format!("{:02x}:", i * 16).cyan());
+    let mut heading = String::from("   ");
+    for i in 0..columns {
+        heading.push_str(&format!(" {:2x}", i));
+    }
+    let mut lines = vec![Line::from(heading)];
+
+    for (i, chunk) in bytes.chunks(columns).enumerate().take(rows.end).skip(rows.start) {
+        let mut line = Vec::with_capacity(columns * 2 + 3);
+        line.push(Span::styled(format!("{:02x}:", i * columns), theme.heading));
 
-        for byte in chunk {
+        for (j, byte) in chunk.iter().enumerate() {
+            let b = i * columns + j;
             line.push(Span::raw(" "));
 
-            let n = format!("{:02x}", byte);
-            let n = if b == ip { n.magenta().bold().underlined() } else { Span::raw(n) };
-            let n = if has_changed(b, bytes, previous_bytes) { n.green() } else { n };
+            let cursor = editing && b == editor.cursor() as usize;
+            let n = match editor.nibble() {
+                Some(high) if cursor => format!("{:x}_", high),
+                _ => format!("{:02x}", byte),
+            };
+            let n = if memory.is_rom(b as u8) { Span::styled(n, theme.rom) } else { Span::raw(n) };
+            let n = match profile.and_then(|profile| heat(profile, b as u8, hottest)) {
+                Some(level) => n.patch_style(theme.heat[level]),
+                None => n,
+            };
+            let n = if uninitialized.contains(&b) { n.patch_style(theme.error) } else { n };
+            let n = if b == ip as usize { n.patch_style(theme.ip) } else { n };
+            let n = if has_changed(b, bytes, previous_bytes) { n.patch_style(theme.changed) } else { n };
+            let n = if editing && editor.is_selected(b) { n.patch_style(theme.selected) } else { n };
+            let n = if cursor { n.patch_style(theme.cursor) } else { n };
 
             line.push(n);
-
-            b += 1;
         }
 
+        line.push(Span::raw(" ".repeat((columns - chunk.len()) * 3 + 1)));
+        line.push(Span::styled(side(chunk, editor.side()), theme.muted));
+
         lines.push(Line::from(line));
     }
 
@@ -34,10 +77,42 @@ pub fn hexdump(ip: u8, bytes: &[u8], previous_bytes: &[u8]) -> impl Widget {
     dump
 }
 
-fn has_changed(index: u8, bytes: &[u8], previous_bytes: &[u8]) -> bool {
-    if index as usize >= previous_bytes.len() {
+/// Returns the address of the byte at the given column and row within the dump's area, as drawn
+/// from row `first`.
+pub fn address(area: Rect, columns: usize, first: usize, x: u16, y: u16) -> Option<usize> {
+    let x = x.checked_sub(area.x + 2)? as usize; // Border and padding
+    let row = y.checked_sub(area.y + 2)? as usize; // Border and heading
+
+    // After the address, each byte is a space and then 2 digits
+    let column = x.checked_sub(4)?;
+    if column % 3 == 2 || column / 3 >= columns || y + 1 >= area.bottom() {
+        return None;
+    }
+
+    Some((first + row) * columns + column / 3)
+}
+
+/// Returns which third of the hottest byte's count the given byte's falls in, if it was touched.
+fn heat(profile: &Profile, adr: u8, hottest: u64) -> Option<usize> {
+    match profile.touches(adr) {
+        0 => None,
+        touches => Some(((touches * 3 - 1) / hottest) as usize),
+    }
+}
+
+fn side(chunk: &[u8], side: Side) -> String {
+    match side {
+        Side::Ascii => chunk.iter()
+            .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' })
+            .collect(),
+        Side::Decimal => chunk.iter().map(|byte| format!("{:4}", byte)).collect(),
+    }
+}
+
+fn has_changed(index: usize, bytes: &[u8], previous_bytes: &[u8]) -> bool {
+    if index >= previous_bytes.len() {
         return true;
     }
 
-    bytes[index as usize] != previous_bytes[index as usize]
+    bytes[index] != previous_bytes[index]
 }
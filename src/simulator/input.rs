@@ -0,0 +1,122 @@
+use super::theme::Theme;
+use ratatui::{
+    prelude::{Line, Span, Widget},
+    widgets::Paragraph,
+};
+use std::collections::VecDeque;
+
+/// The bytes queued and the switches, plus bottom padding.
+pub const INPUT_HEIGHT: u16 = 2 + 1;
+/// The heading before the switches, which start at this column.
+const SWITCHES: &str = " DIP: ";
+
+/// What `In` reads: the bytes typed, oldest first, and once they run out, the DIP switches.
+pub struct Input {
+    queue: VecDeque<u8>,
+    switches: u8,
+    cursor: u8,
+    nibble: Option<u8>,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Left,
+    Right,
+    Toggle,
+    Switch(u8),
+    Nibble(u8),
+    Backspace,
+    Clear,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            switches: 0,
+            cursor: 0,
+            nibble: None,
+        }
+    }
+
+    pub fn read(&mut self) -> u8 {
+        self.pop().unwrap_or(self.switches)
+    }
+
+    /// Take the oldest byte typed, if any.
+    pub fn pop(&mut self) -> Option<u8> {
+        self.queue.pop_front()
+    }
+
+    /// Move between or flip the switches, or queue bytes typed a nibble at a time. The cursor and
+    /// `Switch` count switches from the left, so from the most significant bit.
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Left => self.cursor = self.cursor.saturating_sub(1),
+            Command::Right => self.cursor = (self.cursor + 1).min(7),
+            Command::Toggle => self.switches ^= 0x80 >> self.cursor,
+            Command::Switch(switch) => {
+                self.cursor = switch.min(7);
+                self.switches ^= 0x80 >> self.cursor;
+            },
+            Command::Nibble(low) => match self.nibble.take() {
+                Some(high) => self.queue.push_back(high << 4 | low),
+                None => self.nibble = Some(low),
+            },
+            Command::Backspace => {
+                if self.nibble.take().is_none() {
+                    self.queue.pop_back();
+                }
+            },
+            Command::Clear => {
+                self.queue.clear();
+                self.nibble = None;
+            },
+        }
+    }
+
+    /// Returns the switch drawn at the given column of the panel, counting from the left.
+    pub fn switch(x: u16) -> Option<u8> {
+        let x = x.checked_sub(SWITCHES.len() as u16)?;
+
+        // A space separates the two groups of 4
+        match x {
+            0..=3 => Some(x as u8),
+            5..=8 => Some(x as u8 - 1),
+            _ => None,
+        }
+    }
+
+    /// Show the bytes still to be read, as many as fit in `width`, then the switches and their
+    /// value. The switch at the cursor is highlighted while editing.
+    pub fn render(&self, width: u16, editing: bool, theme: &Theme) -> impl Widget {
+        let slots = (width.saturating_sub(5) / 3) as usize; // "In:" heading, then a byte per slot
+        let mut queue = vec![Span::raw(" "), Span::styled("In:", theme.heading), Span::raw(" ")];
+
+        let pending = self.queue.iter().map(|byte| format!(" {:02x}", byte))
+            .chain(self.nibble.map(|high| format!(" {:x}_", high)))
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            queue.push(Span::styled(" reads the switches", theme.muted));
+        } else if pending.len() > slots {
+            queue.extend(pending.into_iter().take(slots.saturating_sub(1)).map(Span::raw));
+            queue.push(Span::styled(" …", theme.muted));
+        } else {
+            queue.extend(pending.into_iter().map(Span::raw));
+        }
+
+        let mut switches = vec![Span::raw(" "), Span::styled(SWITCHES.trim_start(), theme.heading)];
+        for switch in 0..8 {
+            if switch == 4 {
+                switches.push(Span::raw(" "));
+            }
+
+            let on = self.switches & (0x80 >> switch) != 0;
+            let span = Span::styled(if on { "▀" } else { "▄" }, if on { theme.current } else { theme.muted });
+            switches.push(if editing && switch == self.cursor { span.patch_style(theme.cursor) } else { span });
+        }
+        switches.push(Span::raw(format!(" {:02x}", self.switches)));
+
+        Paragraph::new(vec![Line::from(queue), Line::from(switches)])
+    }
+}
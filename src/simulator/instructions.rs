@@ -1,37 +1,54 @@
-use super::{Edit, Mode};
-use ratatui::prelude::{Line, Stylize};
+use super::{keymap::{Keymap, Scope}, theme::Theme, Edit, Mode};
+use ratatui::prelude::{Line, Span, Stylize};
+use std::ops::Range;
 
-pub fn instructions(mode: &Mode, turbo: bool) -> Line {
-    let mut line = vec![format!(" {}:", mode.to_string()).bold().magenta()];
+/// The columns of a hint's key in the bottom bar, and the binding it triggers when clicked.
+pub type Region = (Range<u16>, Scope, &'static str);
 
-    match mode {
-        Mode::Edit(Edit::IP) => line.extend(vec![
-            " Next ".bold().into(), "<a>".blue().bold(),
-            " Jump × 2 ".bold().into(), "<s>".blue().bold(),
-            " Edit ".bold().into(), "<d>".blue().bold(),
-            " Exit ".bold().into(), "<q> ".blue().bold(),
-        ]),
-        Mode::Edit(Edit::Data) => line.extend(vec![
-            " Increment ".bold().into(), "<a>".blue().bold(),
-            " Shift Left ".bold().into(), "<s>".blue().bold(),
-            " Step ".bold().into(), "<d>".blue().bold(),
-            " Exit ".bold().into(), "<q> ".blue().bold(),
-        ]),
-        Mode::Execute => line.extend(vec![
-            if turbo { " Normal ".bold().into() } else { " Turbo ".bold().into() },
-            "<a>".blue().bold(),
-            " Step ".bold().into(), "<s>".blue().bold(),
-            " Seek ".bold().into(), "<d>".blue().bold(),
-            " Exit ".bold().into(), "<q> ".blue().bold(),
-        ]),
-        Mode::Step => line.extend(vec![
-            " Execute ".bold().into(), "<a>".blue().bold(),
-            " Step ".bold().into(), "<s>".blue().bold(),
-            " Seek ".bold().into(), "<d>".blue().bold(),
-            " Exit ".bold().into(), "<q> ".blue().bold(),
-        ]),
-        _ => {}
+/// The hints for the mode, with the keys currently bound to each, and those of the output history
+/// before the last hint when it is shown. Also returns where each hint is, relative to the start of
+/// the line.
+pub fn instructions<'a>(mode: &'a Mode, history: bool, keymap: &Keymap, theme: &Theme) -> (Line<'a>, Vec<Region>) {
+    let mut line = vec![Span::styled(format!(" {}:", mode), theme.current.bold())];
+    let mut regions = vec![];
+    let Some(scope) = Scope::of(mode) else {
+        return (Line::from(line), regions);
+    };
+
+    if *mode == Mode::Edit(Edit::Memory) || *mode == Mode::Edit(Edit::Input) {
+        line.extend(vec![" Type ".bold(), Span::styled("<0-f>", theme.key)]);
+    }
+
+    let mut hints = scope.hints().iter().map(|hint| (scope, hint)).collect::<Vec<_>>();
+    if history && (scope == Scope::Execute || scope == Scope::Step) {
+        let last = hints.len() - 1;
+        hints.splice(last..last, Scope::Output.hints().iter().map(|hint| (Scope::Output, hint)));
+    }
+
+    let width = |line: &[Span]| line.iter().map(|span| span.width() as u16).sum::<u16>();
+    for (scope, (label, names)) in hints {
+        let keys = names.iter().filter_map(|name| keymap.key(scope, name).map(|key| (*name, key))).collect::<Vec<_>>();
+        if keys.is_empty() {
+            continue;
+        }
+
+        // The label triggers the first key's binding, as does its key, while each other key
+        // triggers its own.
+        let start = width(&line);
+        line.push(format!(" {} ", label).bold());
+        for (i, (name, key)) in keys.iter().enumerate() {
+            let key = match (i, keys.len()) {
+                (0, 1) => format!("<{}>", key),
+                (0, _) => format!("<{}", key),
+                (i, n) if i == n - 1 => format!("/{}>", key),
+                _ => format!("/{}", key),
+            };
+            let start = if i == 0 { start } else { width(&line) };
+            line.push(Span::styled(key, theme.key));
+            regions.push((start..width(&line), scope, *name));
+        }
     }
+    line.push(" ".into());
 
-    Line::from(line)
+    (Line::from(line), regions)
 }
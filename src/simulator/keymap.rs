@@ -0,0 +1,384 @@
+use super::{editor, input, out, source, Action, Edit, Mode};
+use crate::config::Config;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A key and the modifiers held with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+/// The set of bindings in effect, which depends on the mode and on whether the output history is
+/// shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Execute,
+    Step,
+    Seek,
+    Memory,
+    Source,
+    Input,
+    Output,
+}
+
+/// An action that can be bound to keys, known by `name` in the configuration, in each of its
+/// scopes. Its keys can be set for each scope on its own.
+struct Binding {
+    scopes: &'static [Scope],
+    name: &'static str,
+    description: &'static str,
+    keys: &'static [&'static str],
+    action: fn() -> Action,
+}
+
+/// A hint shown in the bottom bar: a label, and the bindings whose keys it lists.
+type Hint = (&'static str, &'static [&'static str]);
+
+/// Which keys trigger which actions in each scope.
+pub struct Keymap {
+    keys: Vec<Vec<Key>>,
+}
+
+/// The scopes where the program runs, which share most of their bindings.
+const RUNNING: &[Scope] = &[Scope::Execute, Scope::Step];
+
+const BINDINGS: &[Binding] = &[
+    Binding { scopes: &[Scope::Execute], name: "pause", description: "Stop executing and step instead", keys: &["s"], action: || Action::Mode(Mode::Step) },
+    Binding { scopes: &[Scope::Step], name: "execute", description: "Execute at the clock speed", keys: &["a"], action: || Action::Mode(Mode::Execute) },
+    Binding { scopes: &[Scope::Step], name: "step", description: "Execute one instruction", keys: &["s"], action: || Action::Step },
+    Binding { scopes: &[Scope::Step], name: "breakpoint", description: "Toggle a breakpoint at the IP", keys: &["b"], action: || Action::Breakpoint(None) },
+
+    Binding { scopes: RUNNING, name: "seek", description: "Move the IP", keys: &["d"], action: || Action::Mode(Mode::Edit(Edit::IP)) },
+    Binding { scopes: RUNNING, name: "source", description: "Edit the source", keys: &["e"], action: || Action::Mode(Mode::Edit(Edit::Source)) },
+    Binding { scopes: RUNNING, name: "input", description: "Type input or flip the switches", keys: &["i"], action: || Action::Mode(Mode::Edit(Edit::Input)) },
+    Binding { scopes: RUNNING, name: "display", description: "Switch the display format", keys: &["f"], action: || Action::Display },
+    Binding { scopes: RUNNING, name: "leds", description: "Show the registers as LEDs", keys: &["l"], action: || Action::Leds },
+    Binding { scopes: RUNNING, name: "profile", description: "Show execution counts and a heatmap", keys: &["p"], action: || Action::Profile },
+    Binding { scopes: RUNNING, name: "irq", description: "Raise an interrupt request", keys: &["r"], action: || Action::Irq },
+    Binding { scopes: RUNNING, name: "faster", description: "Speed the clock up", keys: &["+", "="], action: || Action::Faster },
+    Binding { scopes: RUNNING, name: "slower", description: "Slow the clock down", keys: &["-"], action: || Action::Slower },
+    Binding { scopes: RUNNING, name: "output", description: "Show the output history", keys: &["o"], action: || Action::Output(out::Command::Toggle) },
+    Binding { scopes: RUNNING, name: "help", description: "Show the keys", keys: &["?", "f1"], action: || Action::Help },
+    Binding { scopes: RUNNING, name: "quit", description: "Exit", keys: &["q"], action: || Action::Quit },
+
+    Binding { scopes: &[Scope::Seek], name: "next", description: "Move the IP to the next byte", keys: &["a"], action: || Action::Increment },
+    Binding { scopes: &[Scope::Seek], name: "double", description: "Double the IP", keys: &["s"], action: || Action::Shift },
+    Binding { scopes: &[Scope::Seek], name: "breakpoint", description: "Toggle a breakpoint at the IP", keys: &["b"], action: || Action::Breakpoint(None) },
+    Binding { scopes: &[Scope::Seek], name: "memory", description: "Edit the memory", keys: &["d"], action: || Action::Mode(Mode::Edit(Edit::Memory)) },
+    Binding { scopes: &[Scope::Seek], name: "help", description: "Show the keys", keys: &["?", "f1"], action: || Action::Help },
+    Binding { scopes: &[Scope::Seek], name: "quit", description: "Exit", keys: &["q"], action: || Action::Quit },
+
+    Binding { scopes: &[Scope::Memory], name: "left", description: "Move the cursor left", keys: &["left"], action: || Action::Editor(editor::Command::Left) },
+    Binding { scopes: &[Scope::Memory], name: "right", description: "Move the cursor right", keys: &["right"], action: || Action::Editor(editor::Command::Right) },
+    Binding { scopes: &[Scope::Memory], name: "up", description: "Move the cursor up", keys: &["up"], action: || Action::Editor(editor::Command::Up) },
+    Binding { scopes: &[Scope::Memory], name: "down", description: "Move the cursor down", keys: &["down"], action: || Action::Editor(editor::Command::Down) },
+    Binding { scopes: &[Scope::Memory], name: "select", description: "Start or end a selection", keys: &["v"], action: || Action::Editor(editor::Command::Select) },
+    Binding { scopes: &[Scope::Memory], name: "copy", description: "Copy the selection", keys: &["y"], action: || Action::Editor(editor::Command::Copy) },
+    Binding { scopes: &[Scope::Memory], name: "paste", description: "Paste over the bytes at the cursor", keys: &["p"], action: || Action::Editor(editor::Command::Paste) },
+    Binding { scopes: &[Scope::Memory], name: "insert", description: "Paste, moving the bytes at the cursor along", keys: &["i"], action: || Action::Editor(editor::Command::Insert) },
+    Binding { scopes: &[Scope::Memory], name: "fill", description: "Fill the selection with its first byte", keys: &["="], action: || Action::Editor(editor::Command::Fill) },
+    Binding { scopes: &[Scope::Memory], name: "undo", description: "Undo the last edit", keys: &["u"], action: || Action::Editor(editor::Command::Undo) },
+    Binding { scopes: &[Scope::Memory], name: "side", description: "Switch the side column", keys: &["tab"], action: || Action::Editor(editor::Command::Side) },
+    Binding { scopes: &[Scope::Memory], name: "step", description: "Stop editing", keys: &["esc"], action: || Action::Mode(Mode::Step) },
+    Binding { scopes: &[Scope::Memory], name: "help", description: "Show the keys", keys: &["?", "f1"], action: || Action::Help },
+    Binding { scopes: &[Scope::Memory], name: "quit", description: "Exit", keys: &["q"], action: || Action::Quit },
+
+    Binding { scopes: &[Scope::Source], name: "save", description: "Assemble into RAM and save", keys: &["ctrl-s"], action: || Action::Save },
+    Binding { scopes: &[Scope::Source], name: "step", description: "Stop editing", keys: &["esc"], action: || Action::Mode(Mode::Step) },
+    Binding { scopes: &[Scope::Source], name: "left", description: "Move the cursor left", keys: &["left"], action: || Action::Source(source::Command::Left) },
+    Binding { scopes: &[Scope::Source], name: "right", description: "Move the cursor right", keys: &["right"], action: || Action::Source(source::Command::Right) },
+    Binding { scopes: &[Scope::Source], name: "up", description: "Move the cursor up", keys: &["up"], action: || Action::Source(source::Command::Up) },
+    Binding { scopes: &[Scope::Source], name: "down", description: "Move the cursor down", keys: &["down"], action: || Action::Source(source::Command::Down) },
+    Binding { scopes: &[Scope::Source], name: "home", description: "Move the cursor to the start of the line", keys: &["home"], action: || Action::Source(source::Command::Home) },
+    Binding { scopes: &[Scope::Source], name: "end", description: "Move the cursor to the end of the line", keys: &["end"], action: || Action::Source(source::Command::End) },
+    Binding { scopes: &[Scope::Source], name: "backspace", description: "Delete before the cursor", keys: &["backspace"], action: || Action::Source(source::Command::Backspace) },
+    Binding { scopes: &[Scope::Source], name: "delete", description: "Delete at the cursor", keys: &["delete"], action: || Action::Source(source::Command::Delete) },
+    Binding { scopes: &[Scope::Source], name: "enter", description: "Break the line", keys: &["enter"], action: || Action::Source(source::Command::Enter) },
+    Binding { scopes: &[Scope::Source], name: "help", description: "Show the keys", keys: &["f1"], action: || Action::Help },
+
+    Binding { scopes: &[Scope::Input], name: "left", description: "Move to the switch on the left", keys: &["left"], action: || Action::Input(input::Command::Left) },
+    Binding { scopes: &[Scope::Input], name: "right", description: "Move to the switch on the right", keys: &["right"], action: || Action::Input(input::Command::Right) },
+    Binding { scopes: &[Scope::Input], name: "toggle", description: "Flip the switch", keys: &["space"], action: || Action::Input(input::Command::Toggle) },
+    Binding { scopes: &[Scope::Input], name: "backspace", description: "Drop the last byte typed", keys: &["backspace"], action: || Action::Input(input::Command::Backspace) },
+    Binding { scopes: &[Scope::Input], name: "clear", description: "Drop every byte typed", keys: &["delete"], action: || Action::Input(input::Command::Clear) },
+    Binding { scopes: &[Scope::Input], name: "execute", description: "Execute at the clock speed", keys: &["enter"], action: || Action::Mode(Mode::Execute) },
+    Binding { scopes: &[Scope::Input], name: "step", description: "Stop editing", keys: &["esc"], action: || Action::Mode(Mode::Step) },
+    Binding { scopes: &[Scope::Input], name: "help", description: "Show the keys", keys: &["?", "f1"], action: || Action::Help },
+    Binding { scopes: &[Scope::Input], name: "quit", description: "Exit", keys: &["q"], action: || Action::Quit },
+
+    Binding { scopes: &[Scope::Output], name: "up", description: "Scroll up", keys: &["up"], action: || Action::Output(out::Command::Up) },
+    Binding { scopes: &[Scope::Output], name: "down", description: "Scroll down", keys: &["down"], action: || Action::Output(out::Command::Down) },
+    Binding { scopes: &[Scope::Output], name: "page-up", description: "Scroll up a page", keys: &["pageup"], action: || Action::Output(out::Command::PageUp) },
+    Binding { scopes: &[Scope::Output], name: "page-down", description: "Scroll down a page", keys: &["pagedown"], action: || Action::Output(out::Command::PageDown) },
+    Binding { scopes: &[Scope::Output], name: "first", description: "Scroll to the first value", keys: &["home"], action: || Action::Output(out::Command::Home) },
+    Binding { scopes: &[Scope::Output], name: "last", description: "Follow the newest value", keys: &["end"], action: || Action::Output(out::Command::End) },
+    Binding { scopes: &[Scope::Output], name: "view", description: "Switch the format", keys: &["x"], action: || Action::Output(out::Command::View) },
+    Binding { scopes: &[Scope::Output], name: "write", description: "Write the output to a file", keys: &["w"], action: || Action::WriteOutput },
+];
+
+impl Scope {
+    pub const ALL: [Scope; 7] = [Scope::Execute, Scope::Step, Scope::Seek, Scope::Memory, Scope::Source, Scope::Input, Scope::Output];
+
+    /// The scope of the given mode, or `None` when no keys are bound.
+    pub fn of(mode: &Mode) -> Option<Scope> {
+        match mode {
+            Mode::Edit(Edit::IP) => Some(Scope::Seek),
+            Mode::Edit(Edit::Memory) => Some(Scope::Memory),
+            Mode::Edit(Edit::Source) => Some(Scope::Source),
+            Mode::Edit(Edit::Input) => Some(Scope::Input),
+            Mode::Execute => Some(Scope::Execute),
+            Mode::Step => Some(Scope::Step),
+            Mode::Exit => None,
+        }
+    }
+
+    /// The name of the scope's table in the configuration, under `[keys]`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::Execute => "execute",
+            Scope::Step => "step",
+            Scope::Seek => "seek",
+            Scope::Memory => "memory",
+            Scope::Source => "source",
+            Scope::Input => "input",
+            Scope::Output => "output",
+        }
+    }
+
+    /// The hints shown in the bottom bar, before the ones of the mode when the scope is `Output`.
+    pub fn hints(self) -> &'static [Hint] {
+        match self {
+            Scope::Execute => &[
+                ("Step", &["pause"]),
+                ("Seek", &["seek"]),
+                ("Source", &["source"]),
+                ("Input", &["input"]),
+                ("Display", &["display"]),
+                ("LEDs", &["leds"]),
+                ("Profile", &["profile"]),
+                ("Clock", &["faster", "slower"]),
+                ("Output", &["output"]),
+                ("Help", &["help"]),
+                ("Exit", &["quit"]),
+            ],
+            Scope::Step => &[
+                ("Execute", &["execute"]),
+                ("Step", &["step"]),
+                ("Seek", &["seek"]),
+                ("Source", &["source"]),
+                ("Input", &["input"]),
+                ("Display", &["display"]),
+                ("LEDs", &["leds"]),
+                ("Profile", &["profile"]),
+                ("Clock", &["faster", "slower"]),
+                ("Output", &["output"]),
+                ("Help", &["help"]),
+                ("Exit", &["quit"]),
+            ],
+            Scope::Seek => &[
+                ("Next", &["next"]),
+                ("Jump × 2", &["double"]),
+                ("Memory", &["memory"]),
+                ("Help", &["help"]),
+                ("Exit", &["quit"]),
+            ],
+            Scope::Memory => &[
+                ("Select", &["select"]),
+                ("Copy", &["copy"]),
+                ("Paste", &["paste"]),
+                ("Insert", &["insert"]),
+                ("Fill", &["fill"]),
+                ("Undo", &["undo"]),
+                ("Side", &["side"]),
+                ("Step", &["step"]),
+                ("Help", &["help"]),
+                ("Exit", &["quit"]),
+            ],
+            Scope::Source => &[
+                ("Assemble", &["save"]),
+                ("Step", &["step"]),
+                ("Help", &["help"]),
+            ],
+            Scope::Input => &[
+                ("Switch", &["left", "right"]),
+                ("Flip", &["toggle"]),
+                ("Delete", &["backspace"]),
+                ("Execute", &["execute"]),
+                ("Step", &["step"]),
+                ("Help", &["help"]),
+            ],
+            Scope::Output => &[
+                ("Scroll", &["up", "down"]),
+                ("View", &["view"]),
+                ("Write", &["write"]),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    /// The default bindings, with those in the `[keys.<scope>]` tables of the configuration in
+    /// place of the defaults for the same action.
+    /// ```
+    /// use busyboard::{config::Config, simulator::Keymap};
+    /// let config = Config::parse("[keys.step]\nstep = [\"space\", \"ctrl-n\"]").unwrap();
+    /// assert!(Keymap::from(&config).is_ok());
+    ///
+    /// let config = Config::parse("[keys.step]\nstep = \"hyper-x\"").unwrap();
+    /// assert_eq!(Keymap::from(&config).err().unwrap(), "line 2: unknown key `hyper-x`");
+    /// ```
+    pub fn from(config: &Config) -> Result<Keymap, String> {
+        let mut keymap = Keymap::default();
+
+        for (table, entry) in config.tables("keys") {
+            let error = |message: String| format!("line {}: {}", entry.line + 1, message);
+
+            let scope = Scope::ALL.into_iter().find(|scope| scope.name() == table)
+                .ok_or_else(|| error(format!("unknown mode `{}`", table)))?;
+            let binding = entries().position(|(binding_scope, binding)| binding_scope == scope && binding.name == entry.key)
+                .ok_or_else(|| error(format!("unknown action `{}` in `{}`", entry.key, table)))?;
+
+            keymap.keys[binding] = entry.value.as_list().iter().map(|value| {
+                let key = value.as_str().ok_or_else(|| error(format!("expected a key, found {}", value)))?;
+                Key::parse(key).ok_or_else(|| error(format!("unknown key `{}`", key)))
+            }).collect::<Result<_, _>>()?;
+        }
+
+        Ok(keymap)
+    }
+
+    /// Returns the action bound to the key in the first of the scopes that binds it.
+    pub fn action(&self, scopes: &[Scope], key: KeyEvent) -> Option<Action> {
+        scopes.iter().find_map(|&scope| {
+            self.bindings(scope)
+                .find(|(_, keys)| keys.iter().any(|k| k.matches(key)))
+                .map(|(binding, _)| (binding.action)())
+        })
+    }
+
+    /// Returns the action of the named binding, as if its key had been pressed.
+    pub fn named(&self, scope: Scope, name: &str) -> Option<Action> {
+        self.bindings(scope).find(|(binding, _)| binding.name == name).map(|(binding, _)| (binding.action)())
+    }
+
+    /// Returns the first key bound to the named binding, if any.
+    pub fn key(&self, scope: Scope, name: &str) -> Option<Key> {
+        self.bindings(scope).find(|(binding, _)| binding.name == name).and_then(|(_, keys)| keys.first().copied())
+    }
+
+    /// Returns the name, description and keys of each binding in the scope.
+    pub fn describe(&self, scope: Scope) -> impl Iterator<Item = (&'static str, &'static str, String)> + '_ {
+        self.bindings(scope).map(|(binding, keys)| {
+            let keys = keys.iter().map(Key::to_string).collect::<Vec<_>>().join(", ");
+            (binding.name, binding.description, keys)
+        })
+    }
+
+    fn bindings(&self, scope: Scope) -> impl Iterator<Item = (&'static Binding, &Vec<Key>)> + '_ {
+        entries().zip(&self.keys)
+            .filter(move |((binding_scope, _), _)| *binding_scope == scope)
+            .map(|((_, binding), keys)| (binding, keys))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let keys = entries()
+            .map(|(_, binding)| binding.keys.iter().filter_map(|key| Key::parse(key)).collect())
+            .collect();
+
+        Keymap { keys }
+    }
+}
+
+/// Each binding in each of its scopes, in the order of `Keymap::keys`.
+fn entries() -> impl Iterator<Item = (Scope, &'static Binding)> {
+    BINDINGS.iter().flat_map(|binding| binding.scopes.iter().map(move |&scope| (scope, binding)))
+}
+
+impl Key {
+    /// Parse a key such as `a`, `?`, `ctrl-s`, `alt-left`, `f5`, `pageup` or `space`.
+    pub fn parse(text: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+
+        loop {
+            let (modifier, after) = match rest.split_once('-') {
+                Some((modifier, after)) if !after.is_empty() => (modifier, after),
+                _ => break,
+            };
+
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            rest = after;
+        }
+
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            name if name.len() > 1 && name.starts_with('f') => KeyCode::F(name[1..].parse().ok().filter(|n| (1..=24).contains(n))?),
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            },
+        };
+
+        Some(Key { code, modifiers })
+    }
+
+    /// Whether the event is this key. Shift is part of the character typed, so it is only compared
+    /// for other keys.
+    fn matches(&self, event: KeyEvent) -> bool {
+        let ignored = match event.code {
+            KeyCode::Char(_) => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+
+        event.code == self.code && event.modifiers - ignored == self.modifiers - ignored
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl-"), (KeyModifiers::ALT, "alt-"), (KeyModifiers::SHIFT, "shift-")] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "pgup"),
+            KeyCode::PageDown => write!(f, "pgdn"),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use super::editor::Side;
+use ratatui::{layout::Size, prelude::{Constraint, Layout, Rect}};
+
+pub const REGISTERS_WIDTH: u16 = 13 + 2; // A counter such as "   CY: 999.9k" plus padding
+pub const REGISTERS_HEIGHT: u16 = 8; // Title, AX, IP, C, H, I, IC, CY; SP takes one more
+pub const OUT_HEIGHT: u16 = 2 + 1; // 2 Lines plus bottom padding
+pub const DISPLAY_HEIGHT: u16 = 1 + 3 + 1; // Title, 3 lines of segments, bottom padding
+const DISASSEMBLY_WIDTH: u16 = 24; // Address, mnemonic, operand and annotations, plus padding
+const DUMP_MIN_HEIGHT: u16 = 1 + 1 + 2; // Title + a line + border
+
+pub struct Panes {
+    pub disassembly: Rect,
+    pub registers: Rect,
+    pub out: Rect,
+    pub io: Rect,
+    pub display: Rect,
+    pub dump: Rect,
+    pub columns: usize,
+}
+
+/// Each byte is 2 characters, plus a space (or a colon), horizontal padding, a border and the side column.
+fn dump_width(columns: u16, side: Side) -> u16 {
+    (columns + 1) * 3 + 2 + 2 + side.width(columns)
+}
+
+/// Arrange the panes inside the given area, or return `None` if they do not fit.
+///
+/// Wide terminals get the disassembly in a column of its own, while narrow ones stack the panes
+/// and show 8 or 4 bytes per hex dump row instead of 16. Whatever height is left over after the fixed
+/// size panes is shared between the disassembly and the hex dump, which scroll when it runs out.
+/// The registers pane is `registers_size`, so it can hold either the registers or the LEDs, and the
+/// input and devices panes are `io_height` high together, or 0 to hide them.
+pub fn layout(area: Rect, disassembly_height: u16, len: usize, side: Side, registers_size: Size, io_height: u16) -> Option<Panes> {
+    let fixed_height = OUT_HEIGHT + io_height + DISPLAY_HEIGHT;
+    let dump_height = |columns: usize| len.div_ceil(columns) as u16 + 3; // Title + Lines + border
+
+    if area.width >= DISASSEMBLY_WIDTH + dump_width(16, side) && area.height >= registers_size.height + fixed_height + DUMP_MIN_HEIGHT {
+        let [disassembly, right] = Layout::horizontal([
+            Constraint::Min(DISASSEMBLY_WIDTH),
+            Constraint::Length(dump_width(16, side)),
+        ]).areas(area);
+        let [registers, out, io, display, dump] = Layout::vertical([
+            Constraint::Length(registers_size.height),
+            Constraint::Length(OUT_HEIGHT),
+            Constraint::Length(io_height),
+            Constraint::Length(DISPLAY_HEIGHT),
+            Constraint::Max(dump_height(16)),
+        ]).areas(right);
+
+        return Some(Panes { disassembly, registers, out, io, display, dump, columns: 16 });
+    }
+
+    let columns = [16, 8, 4].into_iter().find(|columns| area.width >= dump_width(*columns, side))?;
+    if area.width < registers_size.width + DISASSEMBLY_WIDTH || area.height < registers_size.height + fixed_height + DUMP_MIN_HEIGHT {
+        return None;
+    }
+
+    let dump_height = dump_height(columns as usize);
+    let available = area.height - fixed_height;
+    let top_height = disassembly_height.max(registers_size.height);
+    let (top_height, dump_height) = if top_height + dump_height <= available {
+        (top_height, dump_height)
+    } else {
+        let dump_height = dump_height.min((available / 2).min(available - registers_size.height).max(DUMP_MIN_HEIGHT));
+        (available - dump_height, dump_height)
+    };
+
+    let [top, out, io, display, dump] = Layout::vertical([
+        Constraint::Length(top_height),
+        Constraint::Length(OUT_HEIGHT),
+        Constraint::Length(io_height),
+        Constraint::Length(DISPLAY_HEIGHT),
+        Constraint::Length(dump_height),
+    ]).areas(area);
+    let [disassembly, registers] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(registers_size.width),
+    ]).areas(top);
+
+    Some(Panes { disassembly, registers, out, io, display, dump, columns: columns as usize })
+}
+
+/// Returns the first of `len` lines to show in `height` rows so that `target` is visible.
+pub fn scroll(target: usize, len: usize, height: usize) -> usize {
+    if len <= height {
+        return 0;
+    }
+
+    target.saturating_sub(height / 2).min(len - height)
+}
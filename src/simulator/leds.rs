@@ -0,0 +1,47 @@
+use super::theme::Theme;
+use crate::eater::{Cpu, Flag};
+use ratatui::{
+    prelude::{Line, Span, Style, Stylize, Widget},
+    widgets::{Block, Padding, Paragraph},
+};
+
+/// A label, 8 LEDs with a space between each, plus horizontal padding.
+pub const LEDS_WIDTH: u16 = 6 + 8 * 2 - 1 + 2;
+/// Title, bus, A, B, IR, MAR, PC and flags.
+pub const LEDS_HEIGHT: u16 = 1 + 7;
+
+/// Shows the bus and registers the way the breadboard computer does, one LED per bit.
+pub fn leds(cpu: &Cpu, theme: &Theme) -> impl Widget {
+    let flags = [Flag::Carry, Flag::Halt, Flag::IllegalHalt].map(|flag| cpu.get(flag));
+
+    Paragraph::new(vec![
+        row("Bus", cpu.bus(), 8, theme.leds[0], theme.unlit),
+        row("A", cpu.a(), 8, theme.leds[1], theme.unlit),
+        row("B", cpu.b(), 8, theme.leds[1], theme.unlit),
+        row("IR", cpu.ir(), 8, theme.leds[2], theme.unlit),
+        row("MAR", cpu.mar(), 8, theme.leds[0], theme.unlit),
+        row("PC", cpu.ip(), 8, theme.leds[3], theme.unlit),
+        row("CHI", flags.iter().fold(0, |bits, &flag| bits << 1 | flag as u8), 3, theme.leds[3], theme.unlit),
+    ])
+        .block(Block::new()
+            .title_top(Line::from(" LEDs ".bold()).left_aligned())
+            .padding(Padding::horizontal(1))
+        )
+}
+
+/// Lights an LED for each of the lowest `bits` bits of `value`, most significant first.
+fn row(label: &str, value: u8, bits: u8, lit: Style, unlit: Style) -> Line<'static> {
+    let mut line = vec![Span::raw(format!("{:<6}", label))];
+
+    line.extend((0..bits).rev().map(|bit| {
+        let led = if bit > 0 { "● " } else { "●" };
+
+        if value & (1 << bit) != 0 {
+            Span::styled(led, lit)
+        } else {
+            Span::styled(led.replace('●', "○"), unlit)
+        }
+    }));
+
+    Line::from(line)
+}
@@ -1,24 +1,65 @@
+mod clock;
+mod devices;
 mod disassemble;
+pub mod editor;
 mod instructions;
+mod help;
 mod hexdump;
+mod input;
+mod keymap;
+mod layout;
+mod leds;
 mod registers;
+mod segments;
 mod out;
-
-use crate::{eater::{Cpu, Flag}, ui::ActionLoop};
-use crossterm::event::{KeyEvent, KeyCode};
+mod source;
+mod theme;
+
+use crate::{eater::{Cpu, Disassembly, Flag, I, Isa}, ui::ActionLoop};
+use clock::Clock;
+use crossterm::event::{KeyEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use editor::{Command, MemoryEditor};
+use devices::Attached;
+use input::Input;
+use out::{History, Out};
+use source::SourceEditor;
 use ratatui::{
-    prelude::{Layout, Rect, Widget},
+    layout::{Constraint, Layout, Size},
+    prelude::{Line, Rect, Stylize, Widget},
     widgets::{Block, Padding, Paragraph},
 };
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::cell::RefCell;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+pub use clock::Speed;
+pub use devices::Devices;
+pub use keymap::Keymap;
+pub use theme::Theme;
 
 pub struct Simulator {
+    attached: Attached,
+    breakpoints: BTreeSet<u8>,
+    clock: Clock,
     cpu: Cpu,
+    editor: MemoryEditor,
+    display: segments::Format,
+    dump_scroll: Option<usize>,
+    help: Option<u16>,
+    history: History,
+    hits: RefCell<Hits>,
+    input: Rc<RefCell<Input>>,
+    keymap: Keymap,
+    leds: bool,
     mode: Mode,
     out: Rc<RefCell<Out>>,
-    rate: Duration,
+    profiling: bool,
+    source: SourceEditor,
+    steps: Rc<Cell<u64>>,
+    theme: Theme,
     ui: Ui,
 }
 
@@ -30,29 +71,52 @@ pub enum Mode {
     Step,
 }
 
-impl Mode {
-    fn is_edit(&self) -> bool {
-        match self {
-            Mode::Edit(..) => true,
-            _ => false,
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, Eq)]
 pub enum Edit {
     IP,
-    Data,
+    Memory,
+    Source,
+    Input,
 }
 
 #[derive(Debug)]
 pub enum Action {
+    Breakpoint(Option<u8>),
+    Display,
+    Editor(Command),
+    Faster,
+    Help,
+    HelpScroll(i16),
     Increment,
+    Input(input::Command),
+    Irq,
+    Leds,
     Mode(Mode),
+    Output(out::Command),
+    Profile,
     Quit,
+    Save,
+    ScrollMemory(isize),
+    Select(u8),
     Shift,
+    Slower,
+    Source(source::Command),
     Step,
-    Turbo,
+    Tick,
+    WriteOutput,
+}
+
+/// Where the last frame drew what can be clicked, so mouse events can be mapped back to it.
+#[derive(Default)]
+struct Hits {
+    disassembly: Rect,
+    lines: Vec<u8>,
+    dump: Rect,
+    dump_first: usize,
+    columns: usize,
+    input: Rect,
+    bar: u16,
+    hints: Vec<(Range<u16>, keymap::Scope, &'static str)>,
 }
 
 pub struct Ui {
@@ -64,50 +128,116 @@ pub struct Ui {
     previous_flag_i: bool,
 }
 
-pub struct Out {
-    data: [u8; 16],
-    n: usize,
-    new: bool,
-}
-
 impl Simulator {
     pub fn from(cpu: Cpu) -> Self {
-        let rate = Duration::from_millis(1000);
-
         let ui = Ui {
             previous_ax: cpu.a(),
-            previous_bytes: cpu.read_bytes(0, cpu.len() as u8).to_vec(),
+            previous_bytes: cpu.read_bytes(0, cpu.len()).to_vec(),
             previous_ip: cpu.ip(),
             previous_flag_c: cpu.get(Flag::Carry),
             previous_flag_h: cpu.get(Flag::Halt),
             previous_flag_i: cpu.get(Flag::IllegalHalt),
         };
 
-        let out = Rc::new(RefCell::new(Out {
-            data: [0; 16],
-            n: 0,
-            new: false,
-        }));
+        let out = Rc::new(RefCell::new(Out::new(out::LIMIT)));
+        let steps = Rc::new(Cell::new(0));
+
+        let input = Rc::new(RefCell::new(Input::new()));
 
         let cpu_out = out.clone();
-        let cpu = cpu.with_out(move |data| {
-            let mut out = cpu_out.borrow_mut();
-            let n = out.n;
+        let cpu_steps = steps.clone();
+        let cpu_input = input.clone();
+        let cpu = cpu.with_access_map().with_profiler().with_external_irq().with_out(move |data| {
+            cpu_out.borrow_mut().push(cpu_steps.get(), data);
+        }).with_in(move || cpu_input.borrow_mut().read());
+
+        Self {
+            attached: Attached::default(),
+            breakpoints: BTreeSet::new(),
+            clock: Clock::new(Speed::Hz(1.0)),
+            cpu,
+            display: segments::Format::Unsigned,
+            dump_scroll: None,
+            editor: MemoryEditor::new(),
+            help: None,
+            history: History::new(),
+            hits: RefCell::new(Hits::default()),
+            input,
+            keymap: Keymap::default(),
+            leds: false,
+            mode: Mode::Execute,
+            out: out.clone(),
+            profiling: false,
+            source: SourceEditor::new(),
+            steps,
+            theme: Theme::default(),
+            ui,
+        }
+    }
+
+    /// Assemble the given source into RAM, opening it in the source editor if it has errors.
+    /// Saving writes the source back to the given path.
+    pub fn with_source(mut self, source: &str, path: Option<PathBuf>) -> Self {
+        self.source = SourceEditor::from(source, path);
+        self.source.assemble(&mut self.cpu);
 
-            out.data[n % 16] = data;
-            out.new = true;
-            out.n += 1;
+        if self.source.has_errors() {
+            self.mode = Mode::Edit(Edit::Source);
+        }
 
-            if n >= 512 {
-                out.n = 256;
-            }
-        });
+        self
+    }
+
+    /// Keep at most `limit` values in the output history, dropping the oldest.
+    pub fn with_output_limit(self, limit: usize) -> Self {
+        *self.out.borrow_mut() = Out::new(limit);
+        self
+    }
+
+    /// Use the given keys, which the hints and help show too.
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
 
-        Self { cpu, rate, mode: Mode::Execute, out: out.clone(), ui }
+    /// Attach the given devices, showing what the LCD and UART display.
+    pub fn with_devices(mut self, devices: Devices) -> Self {
+        (self.cpu, self.attached) = devices.connect(self.cpu, &self.input);
+        self
     }
 
-    pub fn is_turbo(&self) -> bool {
-        self.rate != Duration::from_millis(1000)
+    /// Draw with the given styles.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Run at the given clock speed while executing.
+    pub fn with_speed(mut self, speed: Speed) -> Self {
+        self.clock = Clock::new(speed);
+        self
+    }
+
+    fn is_running(&self) -> bool {
+        self.mode == Mode::Execute && !self.cpu.get(Flag::Halt) && !self.cpu.get(Flag::IllegalHalt)
+    }
+
+    /// Returns where disassembly starts besides address 0: the IP, and the interrupt handler.
+    fn entries(&self) -> Vec<u8> {
+        std::iter::once(self.cpu.ip()).chain(self.cpu.interrupts().map(|interrupts| interrupts.vector())).collect()
+    }
+
+    fn step(&mut self) {
+        if self.cpu.get(Flag::Halt) || self.cpu.get(Flag::IllegalHalt) {
+            return;
+        }
+
+        self.steps.set(self.steps.get() + 1);
+        self.cpu.step();
+
+        if self.mode == Mode::Execute && (self.cpu.non_terminating().is_some() || self.breakpoints.contains(&self.cpu.ip())) {
+            self.mode = Mode::Step;
+        }
     }
 }
 
@@ -115,18 +245,72 @@ impl ActionLoop for Simulator {
     type Action = Action;
 
     fn action(&self, key: KeyEvent) -> Option<Self::Action> {
-        match key.code {
-            KeyCode::Char('a') if self.mode == Mode::Execute => Some(Action::Turbo),
-            KeyCode::Char('s') if self.mode == Mode::Execute => Some(Action::Mode(Mode::Step)),
-            KeyCode::Char('d') if self.mode == Mode::Execute => Some(Action::Mode(Mode::Edit(Edit::IP))),
-            KeyCode::Char('a') if self.mode == Mode::Step => Some(Action::Mode(Mode::Execute)),
-            KeyCode::Char('s') if self.mode == Mode::Step => Some(Action::Step),
-            KeyCode::Char('d') if self.mode == Mode::Step => Some(Action::Mode(Mode::Edit(Edit::IP))),
-            KeyCode::Char('a') if self.mode.is_edit() => Some(Action::Increment),
-            KeyCode::Char('s') if self.mode.is_edit() => Some(Action::Shift),
-            KeyCode::Char('d') if self.mode == Mode::Edit(Edit::IP) => Some(Action::Mode(Mode::Edit(Edit::Data))),
-            KeyCode::Char('d') if self.mode == Mode::Edit(Edit::Data) => Some(Action::Mode(Mode::Step)),
-            KeyCode::Char('q') => Some(Action::Quit),
+        if self.help.is_some() {
+            return match key.code {
+                KeyCode::Up => Some(Action::HelpScroll(-1)),
+                KeyCode::Down => Some(Action::HelpScroll(1)),
+                KeyCode::PageUp => Some(Action::HelpScroll(-10)),
+                KeyCode::PageDown => Some(Action::HelpScroll(10)),
+                _ => Some(Action::Help),
+            };
+        }
+
+        let scope = keymap::Scope::of(&self.mode)?;
+        let scopes = if self.history.is_visible() && (self.mode == Mode::Execute || self.mode == Mode::Step) {
+            vec![keymap::Scope::Output, scope]
+        } else {
+            vec![scope]
+        };
+
+        if let Some(action) = self.keymap.action(&scopes, key) {
+            return Some(action);
+        }
+
+        // Typing is not bound, so any key left over edits the source or memory.
+        match (&self.mode, key.code) {
+            (_, KeyCode::Char(_)) if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => None,
+            (Mode::Edit(Edit::Source), KeyCode::Char(c)) => Some(Action::Source(source::Command::Char(c))),
+            (Mode::Edit(Edit::Memory), KeyCode::Char(c)) => c.to_digit(16).map(|n| Action::Editor(Command::Nibble(n as u8))),
+            (Mode::Edit(Edit::Input), KeyCode::Char(c)) => c.to_digit(16).map(|n| Action::Input(input::Command::Nibble(n as u8))),
+            _ => None,
+        }
+    }
+
+    fn mouse(&self, event: MouseEvent) -> Option<Self::Action> {
+        let (x, y) = (event.column, event.row);
+        let hits = self.hits.borrow();
+        let inside = |area: Rect| area.left() <= x && x < area.right() && area.top() <= y && y < area.bottom();
+        let disassembly = inside(hits.disassembly) && self.mode != Mode::Edit(Edit::Source);
+
+        if self.help.is_some() {
+            return match event.kind {
+                MouseEventKind::ScrollUp => Some(Action::HelpScroll(-1)),
+                MouseEventKind::ScrollDown => Some(Action::HelpScroll(1)),
+                MouseEventKind::Down(_) => Some(Action::Help),
+                _ => None,
+            };
+        }
+
+        match event.kind {
+            MouseEventKind::ScrollUp if disassembly && self.history.is_visible() => Some(Action::Output(out::Command::Up)),
+            MouseEventKind::ScrollDown if disassembly && self.history.is_visible() => Some(Action::Output(out::Command::Down)),
+            MouseEventKind::ScrollUp => Some(Action::ScrollMemory(-1)),
+            MouseEventKind::ScrollDown => Some(Action::ScrollMemory(1)),
+            MouseEventKind::Down(MouseButton::Left) if y == hits.bar => {
+                let (_, scope, name) = hits.hints.iter().find(|(columns, ..)| columns.contains(&x))?;
+                self.keymap.named(*scope, name)
+            },
+            MouseEventKind::Down(MouseButton::Left) if disassembly && !self.history.is_visible() => {
+                hits.lines.get((y - hits.disassembly.y) as usize).map(|adr| Action::Breakpoint(Some(*adr)))
+            },
+            MouseEventKind::Down(MouseButton::Left) if inside(hits.input) && y == hits.input.y + 1 => {
+                Input::switch(x - hits.input.x).map(|switch| Action::Input(input::Command::Switch(switch)))
+            },
+            MouseEventKind::Down(MouseButton::Left) if inside(hits.dump) => {
+                hexdump::address(hits.dump, hits.columns, hits.dump_first, x, y)
+                    .filter(|adr| *adr < self.cpu.len())
+                    .map(|adr| Action::Select(adr as u8))
+            },
             _ => None,
         }
     }
@@ -135,13 +319,13 @@ impl ActionLoop for Simulator {
         self.mode == Mode::Exit
     }
 
-    fn deadline(&self) -> &Duration {
-        &self.rate
+    fn deadline(&self) -> Duration {
+        self.clock.deadline(self.is_running())
     }
 
     fn deadline_expired(&self) -> Option<Self::Action> {
         if self.mode == Mode::Execute {
-            Some(Action::Step)
+            Some(Action::Tick)
         } else {
             None
         }
@@ -149,7 +333,7 @@ impl ActionLoop for Simulator {
 
     fn update(&mut self, action: Self::Action) {
         self.out.borrow_mut().new = false;
-        self.ui.previous_bytes = self.cpu.read_bytes(0, self.cpu.len() as u8).to_vec();
+        self.ui.previous_bytes = self.cpu.read_bytes(0, self.cpu.len()).to_vec();
         self.ui.previous_ax = self.cpu.a();
         self.ui.previous_ip = self.cpu.ip();
         self.ui.previous_flag_c = self.cpu.get(Flag::Carry);
@@ -157,33 +341,84 @@ impl ActionLoop for Simulator {
         self.ui.previous_flag_i = self.cpu.get(Flag::IllegalHalt);
 
          match action {
-            Action::Increment => {
-                match self.mode {
-                    Mode::Edit(Edit::IP) => self.cpu.goto(self.cpu.ip().wrapping_add(1)),
-                    Mode::Edit(Edit::Data) => {
-                        let value = self.cpu.read(self.cpu.ip()).unwrap_or(0_u8);
-                        self.cpu.write(self.cpu.ip(), value.wrapping_add(1))
-                    },
-                    _ => (),
+            Action::Breakpoint(adr) => {
+                let adr = adr.unwrap_or(self.cpu.ip());
+                if !self.breakpoints.remove(&adr) {
+                    self.breakpoints.insert(adr);
                 }
             },
+            Action::Display => self.display = self.display.next(),
+            Action::Editor(command) => {
+                self.editor.apply(command, &mut self.cpu);
+                self.dump_scroll = None;
+            },
+            Action::Faster => self.clock.set_speed(self.clock.speed().faster()),
+            Action::Help => self.help = if self.help.is_some() { None } else { Some(0) },
+            Action::HelpScroll(lines) => self.help = self.help.map(|scroll| scroll.saturating_add_signed(lines)),
+            Action::Increment => self.cpu.goto(self.cpu.ip().wrapping_add(1)),
+            Action::Input(command) => self.input.borrow_mut().apply(command),
+            Action::Irq => self.cpu.irq(),
+            Action::Leds => self.leds = !self.leds,
+            Action::Mode(Mode::Edit(Edit::Memory)) => {
+                self.dump_scroll = None;
+                self.mode = Mode::Edit(Edit::Memory);
+            },
+            Action::Mode(Mode::Edit(Edit::Source)) => {
+                let bytes = self.cpu.read_bytes(0, self.cpu.len());
+                self.source.seed(&crate::eater::disassemble_from(bytes, &self.entries(), self.cpu.isa(), self.cpu.interrupts().is_some()));
+                self.mode = Mode::Edit(Edit::Source);
+            },
+            Action::Mode(Mode::Execute) => {
+                self.clock.start();
+                self.mode = Mode::Execute;
+            },
             Action::Mode(mode) => self.mode = mode,
+            Action::Output(command) => self.history.apply(command, &self.out.borrow()),
+            Action::Profile => self.profiling = !self.profiling,
             Action::Quit => self.mode = Mode::Exit,
-            Action::Shift if self.mode == Mode::Edit(Edit::IP) => self.cpu.goto(self.cpu.ip().wrapping_mul(2)),
-            Action::Shift if self.mode == Mode::Edit(Edit::Data) => {
-                let value = self.cpu.read(self.cpu.ip()).unwrap_or(0_u8);
-                self.cpu.write(self.cpu.ip(), value.wrapping_mul(2))
+            Action::Save => {
+                if self.source.save(&mut self.cpu) {
+                    self.out.borrow_mut().clear();
+                    self.steps.set(0);
+                }
+            },
+            Action::ScrollMemory(rows) => {
+                let hits = self.hits.borrow();
+                let shown = hits.dump.height.saturating_sub(3) as usize; // Title + border
+                let last = self.cpu.len().div_ceil(hits.columns.max(1)).saturating_sub(shown);
+                let first = self.dump_scroll.unwrap_or(hits.dump_first);
+                self.dump_scroll = Some(first.saturating_add_signed(rows).min(last));
             },
-            Action::Shift => (),
-            Action::Step => {
-                self.cpu.step();
+            Action::Select(adr) => {
+                self.editor.goto(adr);
+                self.dump_scroll = None;
+                self.mode = Mode::Edit(Edit::Memory);
+            },
+            Action::Source(command) => self.source.apply(command),
+            Action::Shift => self.cpu.goto(self.cpu.ip().wrapping_mul(2)),
+            Action::Slower => self.clock.set_speed(self.clock.speed().slower()),
+            Action::Step => self.step(),
+            Action::Tick => {
+                // Run the steps the clock owes, but never for longer than a frame, so the screen
+                // and the keyboard stay responsive when the CPU cannot keep up.
+                let due = self.clock.due();
+                let start = Instant::now();
+                let mut steps = 0;
+
+                while self.is_running() {
+                    if due.is_some_and(|due| steps >= due) || (steps % 256 == 255 && start.elapsed() >= clock::FRAME) {
+                        break;
+                    }
+
+                    self.step();
+                    steps += 1;
+                }
+
+                self.clock.record(steps);
             },
-            Action::Turbo => {
-                self.rate = if self.is_turbo() {
-                    Duration::from_millis(1000)
-                } else {
-                    Duration::from_millis(50)
-                };
+            Action::WriteOutput => {
+                let path = self.source.path().map_or(PathBuf::from("output.txt"), |path| path.with_extension("out"));
+                self.history.save(&self.out.borrow(), &path);
             },
          }
     }
@@ -191,52 +426,156 @@ impl ActionLoop for Simulator {
 
 impl ratatui::widgets::WidgetRef for Simulator {
     fn render_ref(&self, area: Rect, buffer: &mut ratatui::prelude::Buffer) {
-        let bytes = self.cpu.read_bytes(0, self.cpu.len() as u8);
+        let bytes = self.cpu.read_bytes(0, self.cpu.len());
 
-        let chrome_height = 2;
-        let instructions = instructions::instructions(&self.mode, self.is_turbo());
+        let (instructions, hints) = instructions::instructions(&self.mode, self.history.is_visible(), &self.keymap, &self.theme);
+        let bar = area.x + 1 + area.width.saturating_sub(2).saturating_sub(instructions.width() as u16) / 2; // Centered between the corners
+        let clock = if self.is_running() {
+            format!(" Clock: {} ({} ips) ", self.clock.speed(), clock::si(self.clock.ips()))
+        } else {
+            format!(" Clock: {} ", self.clock.speed())
+        };
         let chrome = Block::bordered()
             .title("Simulator")
+            .title(Line::from(clock).centered())
             .title_bottom(instructions.centered());
+        let problem = match (self.cpu.fault(), self.cpu.non_terminating(), self.cpu.warnings().last()) {
+            (Some(violation), _, _) => Some(violation.to_string()),
+            (None, Some(detected), _) => Some(detected.to_string()),
+            (None, None, Some(warning)) => Some(warning.to_string()),
+            (None, None, None) => None,
+        };
+        let chrome = match problem {
+            Some(problem) => chrome.title(Line::styled(format!(" {} ", problem), self.theme.error).right_aligned()),
+            None => chrome,
+        };
 
-        let disassembled = crate::eater::disassemble(&bytes);
+        let disassembled = match self.cpu.access_map() {
+            Some(accesses) => crate::eater::disassemble_traced(bytes, &self.entries(), accesses, self.cpu.isa(), self.cpu.interrupts().is_some()),
+            None => crate::eater::disassemble_from(bytes, &self.entries(), self.cpu.isa(), self.cpu.interrupts().is_some()),
+        };
         let disassembly = disassemble::disassemble(
             &disassembled,
             self.cpu.ip(),
-            &bytes,
-            &self.ui.previous_bytes
+            bytes,
+            &self.ui.previous_bytes,
+            self.cpu.access_map(),
+            self.cpu.profile().filter(|_| self.profiling),
+            &self.source,
+            &self.breakpoints,
+            &self.theme,
         );
-        let disassembly_height = disassembly.len() as u16 + 1; // Instructions + padding
+        let disassembly_height = if self.mode == Mode::Edit(Edit::Source) {
+            self.source.len() as u16 + 2 // Title + lines + padding
+        } else if self.history.is_visible() {
+            self.out.borrow().len().min(u16::MAX as usize - 2) as u16 + 2 // Title + values + padding
+        } else {
+            disassembly.len() as u16 + 1 // Instructions + padding
+        };
+
+        let registers_size = if self.leds {
+            Size::new(leds::LEDS_WIDTH, leds::LEDS_HEIGHT)
+        } else {
+            Size::new(layout::REGISTERS_WIDTH, layout::REGISTERS_HEIGHT + (self.cpu.isa() == Isa::Extended) as u16)
+        };
+
+        // Only programs that read input need the input pane
+        let input_height = if self.mode == Mode::Edit(Edit::Input) || disassembled.iter().any(|segment| {
+            matches!(segment, Disassembly::Instruction { instruction: I::In(..), .. })
+        }) {
+            input::INPUT_HEIGHT
+        } else {
+            0
+        };
+
+        let inner = chrome.inner(area);
+        let io_height = input_height + self.attached.height();
+        let Some(panes) = layout::layout(inner, disassembly_height, bytes.len(), self.editor.side(), registers_size, io_height) else {
+            *self.hits.borrow_mut() = Hits::default();
+            Paragraph::new(vec![Line::from("Terminal too small".bold()), Line::from("Resize to continue")])
+                .centered()
+                .render(area, buffer);
+            return;
+        };
+
+        let ip_line = disassemble::line_of(&disassembled, self.cpu.ip());
+        let rows = panes.disassembly.height.saturating_sub(1) as usize; // Minus bottom padding
+        let scroll = layout::scroll(ip_line, disassembly.len(), rows);
+        let lines = disassemble::addresses(&disassembled).into_iter().skip(scroll).take(rows).collect();
+        let scroll = scroll as u16;
         let disassembly = Paragraph::new(disassembly)
-            .block(Block::new().padding(Padding::horizontal(1)));
-
-        let registers_width = 9 + 2; // The word "Registers" plus right padding
-        let register_height = 7; // Title, AX, IP, C, H, I, padding
-        let registers = registers::registers(&self.cpu, &self.ui);
-
-        let out_height = 2 + 1; // 2 Lines plus bottom padding
-        let out = out::out(self.out.borrow());
-
-        // Each byte is 2 characters, plus a space (or a colon), horizontal padding, and a border.
-        let dump_width = 17 * 3 + 2 + 2;
-        let dump_height = 1 + ((bytes.len() + 15) / 16) as u16 + 2; // Title + Lines + border
-        let dump = hexdump::hexdump(self.cpu.ip(), &bytes, &self.ui.previous_bytes);
-
-        let width = dump_width + 2; // Add 2 for the border
-        let height = chrome_height + disassembly_height.max(register_height) + out_height + dump_height;
-        let area = Rect::new(area.x, area.y, width, area.height.min(height));
-        let areas = Layout::vertical(vec![
-            ratatui::prelude::Constraint::Length(disassembly_height),
-            ratatui::prelude::Constraint::Length(out_height),
-            ratatui::prelude::Constraint::Length(dump_height),
-        ]).split(Rect::new(area.x + 1, area.y + 1, area.width - 2, area.height - 2));
-        let register_area = Rect::new(areas[0].width - registers_width, areas[0].y, registers_width, register_height);
+            .scroll((scroll, 0))
+            .block(Block::new().padding(Padding::new(1, 1, 0, 1)));
+        let rows = panes.disassembly.height.saturating_sub(2) as usize; // Minus title and bottom padding
+        let source = self.source.render(self.cpu.ip(), rows, &self.theme);
+        let history = self.history.render(self.out.borrow(), rows, &self.theme);
+
+        let registers = registers::registers(&self.cpu, &self.ui, &self.theme);
+        let leds = leds::leds(&self.cpu, &self.theme);
+
+        let slots = (panes.out.width.saturating_sub(5) / 3) as usize; // "Out:" heading, then a byte per slot
+        let out = out::out(self.out.borrow(), slots, &self.theme);
+
+        let [input_pane, devices_pane] = Layout::vertical([Constraint::Length(input_height), Constraint::Min(0)]).areas(panes.io);
+        let input = self.input.borrow().render(input_pane.width, self.mode == Mode::Edit(Edit::Input), &self.theme);
+        let devices = self.attached.render(devices_pane.width, &self.theme);
+
+        let display = segments::segments(self.out.borrow().last(), self.display, &self.theme);
+
+        let rows = panes.dump.height.saturating_sub(3) as usize; // Title + border
+        let total = bytes.len().div_ceil(panes.columns);
+        let target = if self.mode == Mode::Edit(Edit::Memory) { self.editor.cursor() } else { self.cpu.ip() };
+        let first = match self.dump_scroll {
+            Some(first) => first.min(total.saturating_sub(rows)),
+            None => layout::scroll(target as usize / panes.columns, total, rows),
+        };
+        self.editor.set_columns(panes.columns);
+        let dump = hexdump::hexdump(
+            &self.cpu,
+            &self.ui.previous_bytes,
+            panes.columns,
+            first..first + rows,
+            &self.editor,
+            self.mode == Mode::Edit(Edit::Memory),
+            self.profiling,
+            &self.theme,
+        );
+
+        *self.hits.borrow_mut() = Hits {
+            disassembly: panes.disassembly,
+            lines,
+            dump: panes.dump,
+            dump_first: first,
+            columns: panes.columns,
+            input: input_pane,
+            bar: area.bottom().saturating_sub(1),
+            hints: hints.into_iter()
+                .map(|(columns, scope, name)| ((bar + columns.start).min(area.right() - 1)..(bar + columns.end).min(area.right() - 1), scope, name))
+                .collect(),
+        };
 
         chrome.render(area, buffer);
-        disassembly.render(areas[0], buffer);
-        registers.render(register_area, buffer);
-        out.render(areas[1], buffer);
-        dump.render(areas[2], buffer);
+        if self.mode == Mode::Edit(Edit::Source) {
+            source.render(panes.disassembly, buffer);
+        } else if self.history.is_visible() {
+            history.render(panes.disassembly, buffer);
+        } else {
+            disassembly.render(panes.disassembly, buffer);
+        }
+        if self.leds {
+            leds.render(panes.registers, buffer);
+        } else {
+            registers.render(panes.registers, buffer);
+        }
+        out.render(panes.out, buffer);
+        input.render(input_pane, buffer);
+        devices.render(devices_pane, buffer);
+        display.render(panes.display, buffer);
+        dump.render(panes.dump, buffer);
+
+        if let Some(scroll) = self.help {
+            help::help(&self.keymap, scroll, &self.theme).render(area, buffer);
+        }
     }
 }
 
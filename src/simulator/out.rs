@@ -1,23 +1,226 @@
-use super::Out;
+use super::theme::Theme;
 use ratatui::{
     prelude::{Line, Span, Stylize, Widget},
-    widgets::Paragraph,
+    widgets::{Block, Padding, Paragraph},
 };
-use std::cell::Ref;
+use std::cell::{Cell, Ref};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 
-pub fn out(out: Ref<Out>) -> impl Widget {
-    let mut headings = vec![Span::raw("     ")];
-    let mut data = vec![Span::raw(" "), Span::raw("Out:").cyan()];
+/// How many values are kept by default before the oldest are dropped.
+pub const LIMIT: usize = 1 << 16;
+
+/// Every value output, with the step that output it, up to a limit.
+pub struct Out {
+    log: VecDeque<(u64, u8)>,
+    dropped: usize,
+    limit: usize,
+    pub new: bool,
+}
+
+/// How the output history shows each value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Hex,
+    Unsigned,
+    Signed,
+    Ascii,
+}
+
+/// The scrollable output history shown in place of the disassembly.
+pub struct History {
+    visible: bool,
+    first: Option<usize>,
+    view: View,
+    rows: Cell<usize>,
+    saved: Option<Result<PathBuf, String>>,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Toggle,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    View,
+}
+
+impl Out {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            log: VecDeque::new(),
+            dropped: 0,
+            limit: limit.max(1),
+            new: false,
+        }
+    }
+
+    pub fn push(&mut self, step: u64, value: u8) {
+        if self.log.len() == self.limit {
+            self.log.pop_front();
+            self.dropped += 1;
+        }
+
+        self.log.push_back((step, value));
+        self.new = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+        self.dropped = 0;
+        self.new = false;
+    }
+
+    pub fn last(&self) -> Option<u8> {
+        self.log.back().map(|(_, value)| *value)
+    }
+
+    /// The number of values output, including those dropped to stay within the limit.
+    pub fn len(&self) -> usize {
+        self.dropped + self.log.len()
+    }
+
+    /// The values still kept, with their index among all values output.
+    fn entries(&self) -> impl Iterator<Item = (usize, u64, u8)> + '_ {
+        self.log.iter().enumerate().map(|(i, (step, value))| (self.dropped + i, *step, *value))
+    }
+}
+
+impl View {
+    fn next(self) -> Self {
+        match self {
+            View::Hex => View::Unsigned,
+            View::Unsigned => View::Signed,
+            View::Signed => View::Ascii,
+            View::Ascii => View::Hex,
+        }
+    }
 
-    let n = if out.n < 16 { 0 } else { out.n - 16 };
-    for i in n..(n+16) {
-        let heading = format!(" {:2x}", i);
-        headings.push(Span::raw(heading));
+    fn format(self, value: u8) -> String {
+        match self {
+            View::Hex => format!("{:02x}", value),
+            View::Unsigned => value.to_string(),
+            View::Signed => (value as i8).to_string(),
+            View::Ascii if value.is_ascii_graphic() || value == b' ' => format!("'{}'", value as char),
+            View::Ascii => format!("\\x{:02x}", value),
+        }
     }
+}
+
+impl std::fmt::Display for View {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            View::Hex => write!(f, "hex"),
+            View::Unsigned => write!(f, "dec"),
+            View::Signed => write!(f, "±dec"),
+            View::Ascii => write!(f, "ascii"),
+        }
+    }
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            first: None,
+            view: View::Hex,
+            rows: Cell::new(1),
+            saved: None,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Scroll the history, which follows the newest value until scrolled up.
+    pub fn apply(&mut self, command: Command, out: &Out) {
+        let rows = self.rows.get();
+        let last = out.log.len().saturating_sub(rows);
+        let first = self.first.unwrap_or(last);
+
+        let first = match command {
+            Command::Toggle => {
+                self.visible = !self.visible;
+                self.saved = None;
+                return;
+            },
+            Command::View => {
+                self.view = self.view.next();
+                return;
+            },
+            Command::Up => first.saturating_sub(1),
+            Command::Down => first + 1,
+            Command::PageUp => first.saturating_sub(rows),
+            Command::PageDown => first + rows,
+            Command::Home => 0,
+            Command::End => last,
+        };
+
+        self.first = if first >= last { None } else { Some(first) };
+    }
+
+    /// Write every value kept, one per line after the step that output it, in the current view.
+    pub fn save(&mut self, out: &Out, path: &Path) {
+        let text = out.entries()
+            .map(|(_, step, value)| format!("{} {}\n", step, self.view.format(value)))
+            .collect::<String>();
+
+        self.saved = Some(match std::fs::write(path, text) {
+            Ok(()) => Ok(path.to_path_buf()),
+            Err(error) => Err(format!("could not write {}: {}", path.display(), error)),
+        });
+    }
+
+    /// Render the values kept, numbered, with the step that output each.
+    pub fn render(&self, out: Ref<Out>, rows: usize, theme: &Theme) -> impl Widget {
+        self.rows.set(rows.max(1));
+        let last = out.log.len().saturating_sub(rows);
+        let first = self.first.map_or(last, |first| first.min(last));
+
+        let lines = out.entries().skip(first).take(rows).map(|(n, step, value)| {
+            let value = self.view.format(value);
+            let value = if out.new && n + 1 == out.len() { value.bold().patch_style(theme.changed) } else { value.bold() };
+
+            Line::from(vec![
+                Span::styled(format!("{:>6} ", n), theme.muted),
+                Span::styled(format!("@{:<10} ", step), theme.heading),
+                value,
+            ])
+        }).collect::<Vec<_>>();
+
+        let title = match out.dropped {
+            0 => format!(" Output ({}, {} values) ", self.view, out.len()),
+            dropped => format!(" Output ({}, {} values, first {} dropped) ", self.view, out.len(), dropped),
+        };
+        let title = match &self.saved {
+            Some(Ok(path)) => Line::from(vec![title.bold(), Span::styled(format!("saved to {} ", path.display()), theme.changed)]),
+            Some(Err(error)) => Line::from(vec![title.bold(), Span::styled(format!("{} ", error), theme.error)]),
+            None => Line::from(title.bold()),
+        };
+
+        Paragraph::new(lines)
+            .block(Block::new()
+                .title_top(title.left_aligned())
+                .padding(Padding::new(1, 1, 0, 1))
+            )
+    }
+}
+
+/// Show the last `slots` values output, headed by their index modulo 256.
+pub fn out(out: Ref<Out>, slots: usize, theme: &Theme) -> impl Widget {
+    let slots = slots.max(1);
+    let mut headings = vec![Span::raw("     ")];
+    let mut data = vec![Span::raw(" "), Span::styled("Out:", theme.heading)];
+
+    for (n, _, value) in out.entries().skip(out.log.len().saturating_sub(slots)) {
+        headings.push(Span::raw(format!(" {:2x}", n % 0x100)));
 
-    for i in n..out.n {
-        let byte = format!(" {:02x}", out.data[i % 16]);
-        let byte = if out.new && i == (out.n - 1) { byte.green() } else { byte.into() };
+        let byte = format!(" {:02x}", value);
+        let byte = if out.new && n + 1 == out.len() { Span::styled(byte, theme.changed) } else { byte.into() };
         data.push(byte);
     }
 
@@ -1,33 +1,40 @@
-use crate::eater::{Cpu, Flag};
+use crate::eater::{Cpu, Flag, Isa};
 use ratatui::{
-    prelude::{Line, Stylize, Widget},
+    prelude::{Line, Span, Stylize, Widget},
     widgets::{Block, Padding, Paragraph},
 };
-use super::Ui;
+use super::{clock, theme::Theme, Ui};
 
-pub fn registers(cpu: &Cpu, ui: &Ui) -> impl Widget {
+pub fn registers(cpu: &Cpu, ui: &Ui, theme: &Theme) -> impl Widget {
     let ax = format!("   AX: {:02x}", cpu.a());
-    let ax = if cpu.a() != ui.previous_ax { ax.green() } else { ax.into() };
+    let ax = if cpu.a() != ui.previous_ax { Span::styled(ax, theme.changed) } else { ax.into() };
 
     let ip = format!("   IP: {:02x}", cpu.ip());
-    let ip = ip.magenta();
+    let ip = Span::styled(ip, theme.current);
+
+    // Only the extended instructions use the stack
+    let sp = (cpu.isa() == Isa::Extended).then(|| Line::from(format!("   SP: {:02x}", cpu.sp())));
 
     let c = format!("    C: {:01x}", cpu.get(Flag::Carry) as u8);
-    let c = if cpu.get(Flag::Carry) != ui.previous_flag_c { c.green() } else { c.into() };
+    let c = if cpu.get(Flag::Carry) != ui.previous_flag_c { Span::styled(c, theme.changed) } else { c.into() };
 
     let h = format!("    H: {:01x}", cpu.get(Flag::Halt) as u8);
-    let h = if cpu.get(Flag::Halt) != ui.previous_flag_h { h.green() } else { h.into() };
+    let h = if cpu.get(Flag::Halt) != ui.previous_flag_h { Span::styled(h, theme.changed) } else { h.into() };
 
     let i = format!("    I: {:01x}", cpu.get(Flag::IllegalHalt) as u8);
-    let i = if cpu.get(Flag::IllegalHalt) != ui.previous_flag_i { i.green() } else { i.into() };
+    let i = if cpu.get(Flag::IllegalHalt) != ui.previous_flag_i { Span::styled(i, theme.changed) } else { i.into() };
+
+    // Instructions executed and the clock cycles they took
+    let ic = Span::styled(format!("   IC: {}", clock::si(cpu.instructions() as f64)), theme.muted);
+    let cy = Span::styled(format!("   CY: {}", clock::si(cpu.cycles() as f64)), theme.muted);
 
-    let registers = Paragraph::new(vec![
-        Line::from(ax),
-        Line::from(ip),
+    let registers = Paragraph::new([Line::from(ax), Line::from(ip)].into_iter().chain(sp).chain([
         Line::from(c),
         Line::from(h),
         Line::from(i),
-    ])
+        Line::from(ic),
+        Line::from(cy),
+    ]).collect::<Vec<_>>())
         .block(Block::new()
             .title_top(Line::from(" Registers ".bold()).left_aligned())
             .padding(Padding::horizontal(1))
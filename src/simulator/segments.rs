@@ -0,0 +1,81 @@
+use super::theme::Theme;
+use ratatui::{
+    prelude::{Line, Stylize, Widget},
+    widgets::{Block, Padding, Paragraph},
+};
+
+/// How the output register is shown on the seven-segment display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Unsigned,
+    Signed,
+    Hex,
+}
+
+impl Format {
+    pub fn next(self) -> Self {
+        match self {
+            Format::Unsigned => Format::Signed,
+            Format::Signed => Format::Hex,
+            Format::Hex => Format::Unsigned,
+        }
+    }
+}
+
+pub const DIGITS: usize = 4;
+
+/// Render the value as large seven-segment digits, or blank digits before anything is output.
+pub fn segments(value: Option<u8>, format: Format, theme: &Theme) -> impl Widget {
+    let text = match (value, format) {
+        (None, _) => String::new(),
+        (Some(value), Format::Unsigned) => value.to_string(),
+        (Some(value), Format::Signed) => (value as i8).to_string(),
+        (Some(value), Format::Hex) => format!("{:X}", value),
+    };
+    let text = format!("{:>1$}", text, DIGITS);
+
+    let mut rows = [String::new(), String::new(), String::new()];
+    for c in text.chars() {
+        let s = encode(c);
+        let on = |bit: u8, on: char| if s & (1 << bit) != 0 { on } else { ' ' };
+
+        rows[0].extend([' ', on(0, '_'), ' ', ' ']);
+        rows[1].extend([on(5, '|'), on(6, '_'), on(1, '|'), ' ']);
+        rows[2].extend([on(4, '|'), on(3, '_'), on(2, '|'), ' ']);
+    }
+
+    let label = match format {
+        Format::Unsigned => " Display (dec) ",
+        Format::Signed => " Display (±dec) ",
+        Format::Hex => " Display (hex) ",
+    };
+
+    Paragraph::new(rows.into_iter().map(|row| Line::styled(row, theme.display)).collect::<Vec<_>>())
+        .block(Block::new()
+            .title_top(Line::from(label.bold()).left_aligned())
+            .padding(Padding::horizontal(1)))
+}
+
+/// The segments to light for a character, as bits `gfedcba`.
+fn encode(c: char) -> u8 {
+    match c.to_ascii_uppercase() {
+        '0' => 0x3f,
+        '1' => 0x06,
+        '2' => 0x5b,
+        '3' => 0x4f,
+        '4' => 0x66,
+        '5' => 0x6d,
+        '6' => 0x7d,
+        '7' => 0x07,
+        '8' => 0x7f,
+        '9' => 0x6f,
+        'A' => 0x77,
+        'B' => 0x7c,
+        'C' => 0x39,
+        'D' => 0x5e,
+        'E' => 0x79,
+        'F' => 0x71,
+        '-' => 0x40,
+        _ => 0x00,
+    }
+}
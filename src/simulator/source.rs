@@ -0,0 +1,217 @@
+use super::{layout::scroll, theme::Theme};
+use crate::eater::{assemble, AsmError, Assembly, Cpu, Disassembly};
+use ratatui::{
+    prelude::{Line, Span, Stylize, Widget},
+    widgets::{Block, Padding, Paragraph},
+};
+use std::path::{Path, PathBuf};
+
+/// A text editor for eater assembly, assembled into the CPU's RAM on save.
+pub struct SourceEditor {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    errors: Vec<AsmError>,
+    saved: Option<(Vec<String>, Assembly)>,
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Char(char),
+    Backspace,
+    Delete,
+    Enter,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+}
+
+impl SourceEditor {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+            errors: vec![],
+            saved: None,
+            path: None,
+        }
+    }
+
+    /// Edit the given source which, if a path is given, is written back to it on save.
+    pub fn from(source: &str, path: Option<PathBuf>) -> Self {
+        let lines = source.lines().map(str::to_string).collect::<Vec<_>>();
+        let lines = if lines.is_empty() { vec![String::new()] } else { lines };
+
+        Self { lines, path, ..Self::new() }
+    }
+
+    /// The file the source is saved to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Start from the disassembly of the RAM, unless there is already some source to edit.
+    pub fn seed(&mut self, disassembly: &[Disassembly]) {
+        if self.saved.is_some() || self.lines.iter().any(|line| !line.is_empty()) {
+            return;
+        }
+
+        self.lines = disassembly.iter().map(|segment| match segment {
+            Disassembly::Instruction { instruction, .. } => format!("    {}", instruction),
+            Disassembly::Variable { value, .. } => format!("    db {:#04x}", value),
+            Disassembly::Data { data, .. } => {
+                let data = data.iter().map(|byte| format!("{:#04x}", byte)).collect::<Vec<_>>();
+                format!("    db {}", data.join(", "))
+            },
+        }).collect();
+        self.lines.push(String::new());
+        self.row = 0;
+        self.col = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The saved source line and its number for the byte at the given address.
+    pub fn source_line(&self, adr: u8) -> Option<(usize, &str)> {
+        let (lines, assembly) = self.saved.as_ref()?;
+        let line = assembly.line(adr)?;
+
+        Some((line, lines[line].trim()))
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        let len = self.lines[self.row].chars().count();
+
+        match command {
+            Command::Char(c) => {
+                let at = self.byte_index();
+                self.lines[self.row].insert(at, c);
+                self.col += 1;
+            },
+            Command::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let at = self.byte_index();
+                self.lines[self.row].remove(at);
+            },
+            Command::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.lines[self.row].chars().count();
+                self.lines[self.row].push_str(&line);
+            },
+            Command::Backspace => {},
+            Command::Delete if self.col < len => {
+                let at = self.byte_index();
+                self.lines[self.row].remove(at);
+            },
+            Command::Delete if self.row + 1 < self.lines.len() => {
+                let line = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&line);
+            },
+            Command::Delete => {},
+            Command::Enter => {
+                let at = self.byte_index();
+                let rest = self.lines[self.row].split_off(at);
+                self.lines.insert(self.row + 1, rest);
+                self.row += 1;
+                self.col = 0;
+            },
+            Command::Left => self.col = self.col.min(len).saturating_sub(1),
+            Command::Right => self.col = (self.col + 1).min(len),
+            Command::Up => self.row = self.row.saturating_sub(1),
+            Command::Down => self.row = (self.row + 1).min(self.lines.len() - 1),
+            Command::Home => self.col = 0,
+            Command::End => self.col = len,
+        }
+    }
+
+    /// Assemble the source into the CPU's RAM, or keep the errors to show next to their lines.
+    pub fn assemble(&mut self, cpu: &mut Cpu) {
+        match assemble(&self.lines.join("\n"), cpu.isa(), cpu.interrupts().is_some()) {
+            Ok(assembly) => {
+                cpu.load(assembly.bytes());
+                self.errors.clear();
+                self.saved = Some((self.lines.clone(), assembly));
+            },
+            Err(errors) => self.errors = errors,
+        }
+    }
+
+    /// Assemble the source and, if that succeeds, write it to its file. Returns true if it
+    /// assembled, so the CPU holds the new program, even if the file could not be written.
+    pub fn save(&mut self, cpu: &mut Cpu) -> bool {
+        self.assemble(cpu);
+        if self.has_errors() {
+            return false;
+        }
+
+        if let Some(path) = &self.path {
+            if let Err(error) = std::fs::write(path, self.lines.join("\n") + "\n") {
+                let message = format!("could not write {}: {}", path.display(), error);
+                self.errors.push(AsmError { line: self.row, message });
+            }
+        }
+
+        true
+    }
+
+    /// Render the source with line numbers, the cursor, errors, and the line the IP is on.
+    pub fn render(&self, ip: u8, rows: usize, theme: &Theme) -> impl Widget + '_ {
+        let ip_line = self.source_line(ip).map(|(line, _)| line);
+        let first = scroll(self.row, self.lines.len(), rows);
+
+        let lines = self.lines.iter().enumerate().skip(first).take(rows).map(|(n, text)| {
+            let number = format!("{:3} ", n + 1);
+            let mut line = vec![if ip_line == Some(n) { Span::styled(number, theme.current.bold()) } else { Span::styled(number, theme.muted) }];
+
+            if n == self.row {
+                let col = self.col.min(text.chars().count());
+                let (before, after) = text.split_at(text.char_indices().nth(col).map_or(text.len(), |(i, _)| i));
+                let mut after = after.chars();
+                let cursor = after.next().map_or(" ".to_string(), |c| c.to_string());
+
+                line.push(Span::raw(before.to_string()));
+                line.push(Span::styled(cursor, theme.cursor));
+                line.push(Span::raw(after.as_str().to_string()));
+            } else {
+                line.push(Span::raw(text.clone()));
+            }
+
+            for error in self.errors.iter().filter(|error| error.line == n) {
+                line.push(Span::styled(format!("  ← {}", error.message), theme.error));
+            }
+
+            Line::from(line)
+        }).collect::<Vec<_>>();
+
+        let title = if self.errors.is_empty() { " Source ".bold() } else { " Source ".bold().patch_style(theme.error) };
+        Paragraph::new(lines)
+            .block(Block::new()
+                .title_top(Line::from(title).left_aligned())
+                .padding(Padding::horizontal(1)))
+    }
+
+    fn byte_index(&mut self) -> usize {
+        self.col = self.col.min(self.lines[self.row].chars().count());
+
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map_or(line.len(), |(i, _)| i)
+    }
+}
+
+impl Default for SourceEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
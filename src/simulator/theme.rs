@@ -0,0 +1,154 @@
+use crate::config::Config;
+use ratatui::prelude::{Color, Style, Stylize};
+
+/// The styles every widget draws with, so the simulator can be read on any terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The byte or instruction at the IP.
+    pub ip: Style,
+    /// The IP register, the source line at the IP, and the mode in the bottom bar.
+    pub current: Style,
+    /// What changed since the last step.
+    pub changed: Style,
+    /// Addresses and other labels.
+    pub heading: Style,
+    /// Secondary text, such as line numbers and the ASCII side of the hex dump.
+    pub muted: Style,
+    /// Keys in the hints and help.
+    pub key: Style,
+    /// Errors, warnings, breakpoints and bytes read before they were set.
+    pub error: Style,
+    /// Bytes selected in the memory editor.
+    pub selected: Style,
+    /// The editing cursor.
+    pub cursor: Style,
+    /// Read-only bytes in the hex dump.
+    pub rom: Style,
+    /// Bytes in the hex dump's heatmap, from the least to the most often touched.
+    pub heat: [Style; 3],
+    /// The lit segments of the display.
+    pub display: Style,
+    /// Lit LEDs on the bus, MAR, A and B, IR, and PC and flags, in that order.
+    pub leds: [Style; 4],
+    /// Unlit LEDs.
+    pub unlit: Style,
+}
+
+impl Theme {
+    /// The names of the built-in themes.
+    pub const NAMES: [&'static str; 4] = ["dark", "light", "high-contrast", "monochrome"];
+
+    /// The default, for terminals with a dark background.
+    pub fn dark() -> Self {
+        Self {
+            ip: Style::new().magenta().bold().underlined(),
+            current: Style::new().magenta(),
+            changed: Style::new().green(),
+            heading: Style::new().cyan(),
+            muted: Style::new().dark_gray(),
+            key: Style::new().blue().bold(),
+            error: Style::new().red().bold(),
+            selected: Style::new().on_dark_gray(),
+            cursor: Style::new().reversed(),
+            rom: Style::new().yellow(),
+            heat: [Style::new().on_blue(), Style::new().on_magenta(), Style::new().on_red()],
+            display: Style::new().red().bold(),
+            leds: [Color::Yellow, Color::Red, Color::Blue, Color::Green].map(|color| Style::new().fg(color).bold()),
+            unlit: Style::new().dark_gray(),
+        }
+    }
+
+    /// For terminals with a light background, avoiding cyan and yellow text.
+    pub fn light() -> Self {
+        Self {
+            heading: Style::new().blue(),
+            selected: Style::new().on_gray(),
+            rom: Style::new().fg(Color::Indexed(130)),
+            leds: [Color::Indexed(130), Color::Red, Color::Blue, Color::Green].map(|color| Style::new().fg(color).bold()),
+            ..Self::dark()
+        }
+    }
+
+    /// Bright colors only, with the IP and cursor on a background of their own.
+    pub fn high_contrast() -> Self {
+        Self {
+            ip: Style::new().black().on_light_yellow().bold(),
+            current: Style::new().light_yellow().bold(),
+            changed: Style::new().light_green().bold(),
+            heading: Style::new().light_cyan(),
+            muted: Style::new().white(),
+            key: Style::new().light_cyan().bold(),
+            error: Style::new().light_red().bold(),
+            selected: Style::new().black().on_white(),
+            cursor: Style::new().black().on_light_cyan(),
+            rom: Style::new().light_magenta(),
+            heat: [Color::LightBlue, Color::LightMagenta, Color::LightRed].map(|color| Style::new().black().bg(color)),
+            display: Style::new().light_red().bold(),
+            leds: [Color::LightYellow, Color::LightRed, Color::LightBlue, Color::LightGreen]
+                .map(|color| Style::new().fg(color).bold()),
+            unlit: Style::new().gray(),
+        }
+    }
+
+    /// No colors at all, only bold, underlined and reversed text.
+    pub fn monochrome() -> Self {
+        Self {
+            ip: Style::new().bold().underlined(),
+            current: Style::new().bold(),
+            changed: Style::new().bold(),
+            heading: Style::new(),
+            muted: Style::new(),
+            key: Style::new().bold(),
+            error: Style::new().bold().underlined(),
+            selected: Style::new().underlined(),
+            cursor: Style::new().reversed(),
+            rom: Style::new().underlined().reversed(),
+            heat: [Style::new().underlined(), Style::new().bold().underlined(), Style::new().reversed()],
+            display: Style::new().bold(),
+            leds: [Style::new().bold(); 4],
+            unlit: Style::new(),
+        }
+    }
+
+    /// Returns the built-in theme with the given name.
+    /// ```
+    /// use busyboard::simulator::Theme;
+    /// assert_eq!(Theme::named("light"), Some(Theme::light()));
+    /// assert_eq!(Theme::named("solarized"), None);
+    /// ```
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// The theme named by the top-level `theme` key, or monochrome when `NO_COLOR` is set, or dark.
+    /// ```
+    /// use busyboard::{config::Config, simulator::Theme};
+    /// let config = Config::parse("theme = \"high-contrast\"").unwrap();
+    /// assert_eq!(Theme::from(&config), Ok(Theme::high_contrast()));
+    ///
+    /// let config = Config::parse("theme = \"neon\"").unwrap();
+    /// assert!(Theme::from(&config).is_err());
+    /// ```
+    pub fn from(config: &Config) -> Result<Self, String> {
+        let Some(value) = config.get("", "theme") else {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            return Ok(if no_color { Self::monochrome() } else { Self::dark() });
+        };
+
+        value.as_str().and_then(Self::named).ok_or_else(|| {
+            format!("unknown theme {}, expected one of {}", value, Self::NAMES.join(", "))
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod ui;
 
 pub use ui::{ActionLoop, Ui};
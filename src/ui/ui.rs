@@ -21,12 +21,20 @@ pub trait ActionLoop {
     type Action;
 
     fn action(&self, key: event::KeyEvent) -> Option<Self::Action>;
+    fn mouse(&self, event: event::MouseEvent) -> Option<Self::Action>;
     fn exited(&self) -> bool;
-    fn deadline(&self) -> &Duration;
+    /// How long until the widget wants `deadline_expired` called, measured from now.
+    fn deadline(&self) -> Duration;
     fn deadline_expired(&self) -> Option<Self::Action>;
     fn update(&mut self, action: Self::Action);
 }
 
+impl Default for Ui {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Ui {
     pub fn new() -> Self {
         Self
@@ -45,6 +53,7 @@ impl Ui {
 
     fn setup(&self) -> Result<Tui> {
         stdout().execute(terminal::EnterAlternateScreen)?;
+        stdout().execute(event::EnableMouseCapture)?;
         terminal::enable_raw_mode()?;
         let mut terminal = Tui::new(CrosstermBackend::new(stdout()))?;
         terminal.clear()?;
@@ -75,30 +84,32 @@ impl Ui {
         frame.render_widget(widget, frame.size());
     }
 
-    fn input<'w, W>(&self, widget: &'w W) -> Option<<W as ActionLoop>::Action>
+    fn input<W>(&self, widget: &W) -> Option<<W as ActionLoop>::Action>
     where
         W: ActionLoop,
     {
-        let start = std::time::Instant::now();
-
+        // The widget keeps its own time, so keys pressed while waiting neither delay nor hurry it.
         loop {
-            let deadline = widget.deadline().clone();
-            if start.elapsed() > deadline {
-                return widget.deadline_expired();
-            }
-
-            let deadline = deadline - start.elapsed();
-            if event::poll(deadline).unwrap() {
-                if let event::Event::Key(key) = event::read().unwrap() {
-                    if let Some(action) = widget.action(key) {
-                        return Some(action);
-                    }
+            if event::poll(widget.deadline()).unwrap() {
+                let action = match event::read().unwrap() {
+                    event::Event::Key(key) => widget.action(key),
+                    event::Event::Mouse(mouse) => widget.mouse(mouse),
+                    _ => None,
+                };
+
+                if action.is_some() {
+                    return action;
+                }
+            } else if widget.deadline().is_zero() {
+                if let Some(action) = widget.deadline_expired() {
+                    return Some(action);
                 }
             }
         }
     }
 
     fn cleanup(&self) -> Result<()> {
+        stdout().execute(event::DisableMouseCapture)?;
         stdout().execute(terminal::LeaveAlternateScreen)?;
         terminal::disable_raw_mode()?;
         Ok(())
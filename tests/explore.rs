@@ -0,0 +1,30 @@
+use busyboard::eater::{Cpu, Explorer, I};
+
+#[test]
+fn eater_explore_countdown() {
+    // Count down from the input by the step size, outputting each value until zero.
+    let cpu = Cpu::from_asm(vec![
+        I::lda(12), // 0
+        I::out(),   // 2
+        I::jpz(11), // 3
+        I::sub(13), // 5
+        I::sta(12), // 7
+        I::jmp(0),  // 9
+        I::hlt(),   // 11
+    ], vec![
+        0x00, // n
+        0x00, // step
+    ]);
+
+    let exploration = Explorer::new(&cpu)
+        .input(12, 0..16)
+        .input(13, 0..4)
+        .explore();
+
+    assert!(exploration.complete);
+    assert!(!exploration.can_fault());
+    assert_eq!(exploration.halt, Some(vec![(12, 0), (13, 0)]));
+    assert_eq!(exploration.loops, Some(vec![(12, 1), (13, 0)]));
+    assert!((0..16).all(|n| exploration.outputs.contains_key(&n)));
+    assert_eq!(exploration.outputs[&0xff], vec![(12, 1), (13, 2)]);
+}